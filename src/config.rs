@@ -1,19 +1,145 @@
 use clap::{Parser, Subcommand};
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
 use std::{fs, path::PathBuf};
 
+use crate::distributed::Simulation;
+use crate::report::ReportFormat;
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct Config {
     pub global: GlobalConfig,
     pub multi_target: Option<MultiTargetConfig>,
     pub single_target: Option<SingleTargetConfig>,
+    pub scenario: Option<ScenarioConfig>,
+    #[serde(default)]
+    pub personas: Vec<PersonaConfig>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct GlobalConfig {
     pub api_url: String,
     pub jwt_secret: String,
+    #[serde(default)]
+    pub connection_mode: ConnectionMode,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub timeouts: TimeoutConfig,
+    #[serde(default)]
+    pub concurrency: ConcurrencyConfig,
+    #[serde(default)]
+    pub think_time: ThinkTimeConfig,
+}
+
+/// Whether virtual users keep connections alive or open a new one per request
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionMode {
+    #[default]
+    Pooled,
+    Fresh,
+}
+
+/// Pause of a virtual user between actions, like a human reading the page
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum ThinkTimeConfig {
+    #[default]
+    None,
+    Fixed {
+        ms: u64,
+    },
+    Uniform {
+        min_ms: u64,
+        max_ms: u64,
+    },
+    /// Negative samples are treated as no pause
+    Normal {
+        mean_ms: u64,
+        stdev_ms: u64,
+    },
+    Exponential {
+        mean_ms: u64,
+    },
+}
+
+/// Retry policy applied to every API request
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Total attempts including the first one, 1 disables retries
+    pub max_attempts: u32,
+    pub backoff_base_ms: u64,
+    pub backoff_cap_ms: u64,
+    /// Fraction of the backoff that is randomized, from 0.0 to 1.0
+    pub jitter: f64,
+    pub retry_statuses: Vec<u16>,
+    /// Also retry connect errors, timeouts and broken response bodies
+    pub retry_network_errors: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 3,
+            backoff_base_ms: 200,
+            backoff_cap_ms: 10_000,
+            jitter: 0.5,
+            retry_statuses: vec![429, 502, 503, 504],
+            retry_network_errors: true,
+        }
+    }
+}
+
+/// Request timeouts, read and total can be overridden per logical endpoint
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    pub connect_ms: u64,
+    /// Max wait for the response headers and between body chunks
+    pub read_ms: u64,
+    /// Max duration of a single attempt from sending to the full body
+    pub total_ms: u64,
+    /// Keyed by endpoint name, ie: `issues.list`
+    pub endpoints: HashMap<String, EndpointTimeouts>,
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct EndpointTimeouts {
+    pub read_ms: Option<u64>,
+    pub total_ms: Option<u64>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        TimeoutConfig {
+            connect_ms: 5_000,
+            read_ms: 30_000,
+            total_ms: 60_000,
+            endpoints: HashMap::new(),
+        }
+    }
+}
+
+/// Max requests in flight, extra requests wait for a free slot
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct ConcurrencyConfig {
+    /// Across all virtual users
+    pub max_in_flight: usize,
+    pub max_in_flight_per_user: usize,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        ConcurrencyConfig {
+            max_in_flight: 100,
+            max_in_flight_per_user: 20,
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -21,12 +147,161 @@ pub struct MultiTargetConfig {
     pub users: Vec<Credential>,
     pub issue_count: u32,
     pub issue_type: Option<String>,
+    /// Load profile of the user simulations, without stages every user runs
+    /// once from the start
+    #[serde(default)]
+    pub stages: Vec<LoadStage>,
+    #[serde(default)]
+    pub backlog: BacklogConfig,
+}
+
+/// How users plan backlog issues into the active or next sprint
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct BacklogConfig {
+    /// Chance in percent that a backlog issue is picked
+    pub pick_chance: u32,
+    /// Max total estimate of the sprint, picks that do not fit are skipped
+    pub sprint_capacity: u32,
+}
+
+impl Default for BacklogConfig {
+    fn default() -> Self {
+        BacklogConfig {
+            pick_chance: 30,
+            sprint_capacity: 40,
+        }
+    }
+}
+
+/// Moves the number of active virtual users linearly from the target of the
+/// previous stage, or zero, to this target
+#[derive(Clone, Deserialize, Debug)]
+pub struct LoadStage {
+    pub name: Option<String>,
+    /// Human readable, ie: `5m` or `1h 30m`
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub duration: Duration,
+    /// Active virtual users at the end of the stage, credentials are shared
+    /// when there are more virtual users than users
+    pub target: usize,
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    humantime::parse_duration(&value).map_err(serde::de::Error::custom)
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct Credential {
     pub username: String,
     pub password: String,
+    /// Name of the persona the user acts as, otherwise the scenario applies
+    pub persona: Option<String>,
+}
+
+/// User journey that replaces the built-in rounds of the user simulations
+#[derive(Clone, Deserialize, Debug)]
+pub struct ScenarioConfig {
+    pub name: Option<String>,
+    /// Run in order on every pass, later steps act on what earlier steps loaded
+    pub steps: Vec<ScenarioStep>,
+}
+
+/// Kind of user with its own action mix, ie: a developer moving issues or a
+/// stakeholder only reading
+#[derive(Clone, Deserialize, Debug)]
+pub struct PersonaConfig {
+    pub name: String,
+    pub steps: Vec<ScenarioStep>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ScenarioStep {
+    pub task: ScenarioTask,
+    /// Chance in percent that the step runs on a pass
+    #[serde(default = "default_step_weight")]
+    pub weight: u32,
+    /// How many times the step runs in a row
+    #[serde(default = "default_step_loops")]
+    pub loops: u32,
+    #[serde(default)]
+    pub when: StepCondition,
+}
+
+fn default_step_weight() -> u32 {
+    100
+}
+
+fn default_step_loops() -> u32 {
+    1
+}
+
+/// Built-in tasks a scenario step can run
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioTask {
+    /// Lists all projects and picks a random one to work on
+    FetchProjects,
+    FetchProject,
+    FetchStatuses,
+    FetchLabels,
+    FetchEpics,
+    FetchMembers,
+    FetchSprints,
+    /// Loads the issues of the active sprint board
+    FetchBoard,
+    /// Loads the first page of the backlog
+    FetchBacklog,
+    FetchIssues,
+    FetchIssue,
+    FetchComments,
+    FetchNotifications,
+    FetchActivityLogs,
+    FetchAccount,
+    CreateIssue,
+    CreateEpic,
+    /// Completes the active sprint when done and starts the next planned one
+    CycleSprints,
+    /// Moves a random subset of the board issues one status forward
+    MoveBoardIssues,
+    /// Plans backlog issues into the active or next sprint up to its capacity
+    GroomBacklog,
+    /// Changes random fields of a loaded issue and checks the response
+    UpdateIssue,
+    MoveIssue,
+    CommentIssue,
+    FollowIssue,
+}
+
+impl ScenarioTask {
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self,
+            ScenarioTask::CreateIssue
+                | ScenarioTask::CreateEpic
+                | ScenarioTask::CycleSprints
+                | ScenarioTask::MoveBoardIssues
+                | ScenarioTask::GroomBacklog
+                | ScenarioTask::UpdateIssue
+                | ScenarioTask::MoveIssue
+                | ScenarioTask::CommentIssue
+                | ScenarioTask::FollowIssue
+        )
+    }
+}
+
+/// Skips the step unless earlier steps loaded what it needs
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepCondition {
+    #[default]
+    Always,
+    HasProject,
+    HasIssues,
+    HasEpics,
+    HasMembers,
+    HasLabels,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -47,7 +322,12 @@ impl Config {
             }
         };
 
-        let config: Config = match toml::from_str(toml_string.as_str()) {
+        Config::parse(toml_string.as_str())
+    }
+
+    /// Parses and validates the content of a config file
+    pub fn parse(toml_string: &str) -> Result<Config, &'static str> {
+        let config: Config = match toml::from_str(toml_string) {
             Ok(value) => value,
             Err(err) => {
                 println!("{:?}", err);
@@ -60,7 +340,46 @@ impl Config {
             return Err("Either single target or multi-target config must be present.");
         }
 
-        let issue_types = vec![
+        let retry = &config.global.retry;
+        if retry.max_attempts == 0 {
+            return Err("Retry max attempts must be at least 1.");
+        }
+        if !(0.0..=1.0).contains(&retry.jitter) {
+            return Err("Retry jitter must be between 0.0 to 1.0");
+        }
+        if retry.backoff_base_ms > retry.backoff_cap_ms {
+            return Err("Retry backoff base must not exceed the backoff cap.");
+        }
+
+        let timeouts = &config.global.timeouts;
+        let endpoint_timeouts = timeouts
+            .endpoints
+            .values()
+            .flat_map(|endpoint| [endpoint.read_ms, endpoint.total_ms])
+            .flatten();
+        let mut all_timeouts = [timeouts.connect_ms, timeouts.read_ms, timeouts.total_ms]
+            .into_iter()
+            .chain(endpoint_timeouts);
+        if all_timeouts.any(|value| value == 0) {
+            return Err("Timeouts must be greater than 0.");
+        }
+
+        let concurrency = &config.global.concurrency;
+        if concurrency.max_in_flight == 0 || concurrency.max_in_flight_per_user == 0 {
+            return Err("Max in flight requests must be greater than 0.");
+        }
+
+        match config.global.think_time {
+            ThinkTimeConfig::Uniform { min_ms, max_ms } if min_ms > max_ms => {
+                return Err("Think time min_ms must not be greater than max_ms.");
+            }
+            ThinkTimeConfig::Exponential { mean_ms: 0 } => {
+                return Err("Think time mean_ms must be greater than 0.");
+            }
+            _ => {}
+        }
+
+        let issue_types = [
             String::from("initiative"),
             String::from("epic"),
             String::from("user_story"),
@@ -76,13 +395,48 @@ impl Config {
             if multi_target.users.is_empty() {
                 return Err("At least one user must be present in multi-target config.");
             }
-            if multi_target.issue_count == 0 || multi_target.issue_count > 100 {
-                return Err("Issue count must be between 1 to 100");
+            if multi_target.issue_count == 0 {
+                return Err("Issue count must be greater than 0.");
+            }
+            if let Some(issue_type) = &multi_target.issue_type
+                && !issue_types.contains(issue_type)
+            {
+                return Err("Issue type is invalid.");
+            }
+            if multi_target.backlog.pick_chance > 100 {
+                return Err("Backlog pick chance must be between 0 to 100");
+            }
+            if multi_target.backlog.sprint_capacity == 0 {
+                return Err("Sprint capacity must be greater than 0.");
+            }
+        }
+
+        // Validate scenario and persona steps
+        if let Some(scenario) = &config.scenario {
+            if scenario.steps.is_empty() {
+                return Err("At least one step must be present in the scenario.");
             }
-            if let Some(issue_type) = &multi_target.issue_type {
-                if !issue_types.contains(&issue_type) {
-                    return Err("Issue type is invalid.");
-                }
+            validate_steps(&scenario.steps)?;
+        }
+        for (index, persona) in config.personas.iter().enumerate() {
+            if persona.steps.is_empty() {
+                return Err("At least one step must be present in every persona.");
+            }
+            if config.personas[..index]
+                .iter()
+                .any(|other| other.name == persona.name)
+            {
+                return Err("Persona names must be unique.");
+            }
+            validate_steps(&persona.steps)?;
+        }
+        if let Some(multi_target) = &config.multi_target {
+            let mut personas = multi_target
+                .users
+                .iter()
+                .filter_map(|user| user.persona.as_ref());
+            if personas.any(|name| config.persona(name).is_none()) {
+                return Err("User persona must be one of the configured personas.");
             }
         }
 
@@ -91,20 +445,47 @@ impl Config {
             if single_target.project_id.is_empty() {
                 return Err("Project ID must be present in single-target config.");
             }
-            if single_target.issue_count == 0 || single_target.issue_count > 100 {
-                return Err("Issue count must be between 1 to 100");
+            if single_target.issue_count == 0 {
+                return Err("Issue count must be greater than 0.");
             }
 
             // Validate issue type if present
-            if let Some(issue_type) = &single_target.issue_type {
-                if !issue_types.contains(&issue_type) {
-                    return Err("Issue type is invalid.");
-                }
+            if let Some(issue_type) = &single_target.issue_type
+                && !issue_types.contains(issue_type)
+            {
+                return Err("Issue type is invalid.");
             }
         }
 
         Ok(config)
     }
+
+    pub fn persona(&self, name: &str) -> Option<&PersonaConfig> {
+        self.personas.iter().find(|persona| persona.name == name)
+    }
+
+    /// Steps the user runs, none means the built-in rounds
+    pub fn scenario_of(&self, user: &Credential) -> Option<ScenarioConfig> {
+        match user.persona.as_deref().and_then(|name| self.persona(name)) {
+            Some(persona) => Some(ScenarioConfig {
+                name: Some(persona.name.clone()),
+                steps: persona.steps.clone(),
+            }),
+            None => self.scenario.clone(),
+        }
+    }
+}
+
+fn validate_steps(steps: &[ScenarioStep]) -> Result<(), &'static str> {
+    for step in steps.iter() {
+        if step.weight > 100 {
+            return Err("Scenario step weight must be between 0 to 100");
+        }
+        if step.loops == 0 {
+            return Err("Scenario step loops must be greater than 0.");
+        }
+    }
+    Ok(())
 }
 
 /// CLI tool to create issues into a project
@@ -115,6 +496,35 @@ pub struct Args {
     #[arg(short, long, value_name = "FILE.toml")]
     pub config: PathBuf,
 
+    /// Write a machine-readable run report into this file
+    #[arg(short, long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// Report format, guessed from the report file extension when omitted
+    #[arg(long, value_enum)]
+    pub report_format: Option<ReportFormat>,
+
+    /// How often to print live progress, ie: 5s, 1m or 0s to disable
+    #[arg(long, value_name = "DURATION", default_value = "5s", value_parser = humantime::parse_duration)]
+    pub progress_interval: Duration,
+
+    /// Stop the user simulations and crawls after this long, ie: 30m or 2h
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub duration: Option<Duration>,
+
+    /// Rounds per virtual user, or passes over all issues for the crawls
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub iterations: Option<u32>,
+
+    /// How long requests in flight may take to finish after Ctrl-C or SIGTERM
+    #[arg(long, value_name = "DURATION", default_value = "10s", value_parser = humantime::parse_duration)]
+    pub shutdown_grace: Duration,
+
+    /// Seed of every random choice and generated data, a random one is logged
+    /// when omitted so that any run can be replayed
+    #[arg(long)]
+    pub seed: Option<u64>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -136,6 +546,211 @@ pub enum Commands {
     /// Simulate all users doing random actions as if they are working in their projects
     UsersReadWrite,
 
-    /// Simulate all users doing random actions as if they are working in their projects
+    /// Simulate all users only browsing their projects, issues and account pages
     UsersReadonly,
+
+    /// Split a user simulation across workers and merge their stats into one report
+    Coordinator {
+        /// Address the workers connect to
+        #[arg(long, default_value = "127.0.0.1:7070")]
+        bind: String,
+
+        /// Number of workers to wait for before starting
+        #[arg(long)]
+        workers: usize,
+
+        #[arg(long, value_enum, default_value = "users-read-write")]
+        simulation: Simulation,
+    },
+
+    /// Run the users handed out by a coordinator
+    Worker {
+        /// Address of the coordinator
+        #[arg(long, default_value = "127.0.0.1:7070")]
+        coordinator: String,
+    },
+}
+
+impl Commands {
+    /// Subcommand as typed on the command line, ie: `users-read-write`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::CreateSeedProject => "create-seed-project",
+            Commands::CreateIssues => "create-issues",
+            Commands::CrawlAllIssues => "crawl-all-issues",
+            Commands::CrawlIssues => "crawl-issues",
+            Commands::UsersReadWrite => "users-read-write",
+            Commands::UsersReadonly => "users-readonly",
+            Commands::Coordinator { .. } => "coordinator",
+            Commands::Worker { .. } => "worker",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLOBAL: &str = r#"
+        [global]
+        api_url = "http://localhost/api"
+        jwt_secret = "secret"
+
+        [multi_target]
+        users = [{ username = "alice", password = "secret" }]
+        issue_count = 5
+    "#;
+
+    #[test]
+    fn test_parse_scenario() {
+        let config = Config::parse(&format!(
+            r#"{}
+            [scenario]
+            name = "triage"
+
+            [[scenario.steps]]
+            task = "fetch_projects"
+
+            [[scenario.steps]]
+            task = "fetch_issue"
+            weight = 50
+            loops = 3
+            when = "has_issues"
+            "#,
+            GLOBAL
+        ))
+        .unwrap();
+
+        let scenario = config.scenario.as_ref().unwrap();
+        assert_eq!(scenario.name.as_deref(), Some("triage"));
+        assert_eq!(scenario.steps.len(), 2);
+
+        // Weight, loops and condition have defaults
+        let first = &scenario.steps[0];
+        assert_eq!(first.task, ScenarioTask::FetchProjects);
+        assert_eq!((first.weight, first.loops), (100, 1));
+        assert_eq!(first.when, StepCondition::Always);
+
+        let second = &scenario.steps[1];
+        assert_eq!(second.task, ScenarioTask::FetchIssue);
+        assert_eq!((second.weight, second.loops), (50, 3));
+        assert_eq!(second.when, StepCondition::HasIssues);
+    }
+
+    #[test]
+    fn test_invalid_scenario_steps() {
+        let parse = |steps: &str| Config::parse(&format!("{}\n[scenario]\n{}", GLOBAL, steps));
+
+        assert_eq!(
+            parse("steps = []").unwrap_err(),
+            "At least one step must be present in the scenario."
+        );
+        assert_eq!(
+            parse(r#"steps = [{ task = "fetch_issues", weight = 101 }]"#).unwrap_err(),
+            "Scenario step weight must be between 0 to 100"
+        );
+        assert_eq!(
+            parse(r#"steps = [{ task = "fetch_issues", loops = 0 }]"#).unwrap_err(),
+            "Scenario step loops must be greater than 0."
+        );
+        assert_eq!(
+            parse(r#"steps = [{ task = "unknown" }]"#).unwrap_err(),
+            "Unable to parse config file."
+        );
+        assert!(parse(r#"steps = [{ task = "fetch_issues", weight = 0 }]"#).is_ok());
+    }
+
+    #[test]
+    fn test_parse_personas() {
+        let config = Config::parse(
+            r#"
+            [global]
+            api_url = "http://localhost/api"
+            jwt_secret = "secret"
+
+            [multi_target]
+            users = [
+                { username = "alice", password = "secret", persona = "developer" },
+                { username = "bob", password = "secret" },
+            ]
+            issue_count = 5
+
+            [scenario]
+            steps = [{ task = "fetch_projects" }]
+
+            [[personas]]
+            name = "developer"
+            steps = [{ task = "fetch_projects" }, { task = "move_issue", weight = 30 }]
+
+            [[personas]]
+            name = "stakeholder"
+            steps = [{ task = "fetch_notifications" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.personas.len(), 2);
+
+        // Users without a persona follow the scenario
+        let users = &config.multi_target.as_ref().unwrap().users;
+        let developer = config.scenario_of(&users[0]).unwrap();
+        assert_eq!(developer.name.as_deref(), Some("developer"));
+        assert_eq!(developer.steps[1].task, ScenarioTask::MoveIssue);
+        assert_eq!(developer.steps[1].weight, 30);
+
+        let scenario = config.scenario_of(&users[1]).unwrap();
+        assert_eq!(scenario.name, None);
+        assert_eq!(scenario.steps.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_personas() {
+        let parse = |personas: &str| Config::parse(&format!("{}\n{}", GLOBAL, personas));
+
+        assert_eq!(
+            parse(
+                r#"
+                [[personas]]
+                name = "developer"
+                steps = []
+                "#
+            )
+            .unwrap_err(),
+            "At least one step must be present in every persona."
+        );
+        assert_eq!(
+            parse(
+                r#"
+                [[personas]]
+                name = "developer"
+                steps = [{ task = "fetch_issues" }]
+
+                [[personas]]
+                name = "developer"
+                steps = [{ task = "move_issue" }]
+                "#
+            )
+            .unwrap_err(),
+            "Persona names must be unique."
+        );
+        assert_eq!(
+            parse(
+                r#"
+                [[personas]]
+                name = "developer"
+                steps = [{ task = "fetch_issues", loops = 0 }]
+                "#
+            )
+            .unwrap_err(),
+            "Scenario step loops must be greater than 0."
+        );
+
+        let unknown = GLOBAL.replace(
+            r#"password = "secret""#,
+            r#"password = "secret", persona = "ghost""#,
+        );
+        assert_eq!(
+            Config::parse(&unknown).unwrap_err(),
+            "User persona must be one of the configured personas."
+        );
+    }
 }
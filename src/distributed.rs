@@ -0,0 +1,408 @@
+use anyhow::{Context, anyhow};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tokio::time::{sleep, timeout};
+use tracing::{error, info, warn};
+
+use crate::{
+    config::{Config, Credential},
+    error::Result,
+    stats::{StatsCollector, StatsData},
+    tasks::{
+        client::ClientFactory,
+        runner::{RunLimits, run_users_read_write, run_users_readonly},
+    },
+};
+
+// Coordinator and workers must agree on messages and stats layout
+const PROTOCOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Workers usually start before the coordinator, keep trying for a while
+const CONNECT_ATTEMPTS: u32 = 30;
+
+// Workers say hello right after connecting, anything else must not hold up
+// the other workers
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Extra time the coordinator waits for reports after a shutdown signal, on
+/// top of the grace the workers give to their requests in flight
+pub const REPORT_GRACE: Duration = Duration::from_secs(10);
+
+/// User simulations that can be split across workers
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Simulation {
+    UsersReadWrite,
+    UsersReadonly,
+}
+
+/// Exchanged between coordinator and workers, one JSON object per line
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    /// First message of a worker
+    Hello {
+        version: String,
+    },
+    /// Only usernames are sent, passwords come from the config of the worker
+    Assign {
+        simulation: Simulation,
+        usernames: Vec<String>,
+        seed: u64,
+        duration_ms: Option<u64>,
+        iterations: Option<u32>,
+    },
+    Ready,
+    Start,
+    Stop,
+    /// Stats of the worker and the time from `Start` to the end of its run
+    Report {
+        stats: StatsData,
+        elapsed_ms: u64,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+struct MessageReader {
+    peer: String,
+    lines: Lines<BufReader<OwnedReadHalf>>,
+}
+
+struct MessageWriter {
+    writer: OwnedWriteHalf,
+}
+
+impl MessageReader {
+    async fn receive(&mut self) -> Result<Message> {
+        let Some(line) = self.lines.next_line().await? else {
+            return Err(anyhow!("{} closed the connection.", self.peer));
+        };
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+impl MessageWriter {
+    async fn send(&mut self, message: &Message) -> Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+}
+
+fn split(stream: TcpStream) -> Result<(MessageReader, MessageWriter)> {
+    let peer = stream.peer_addr()?.to_string();
+    let (reader, writer) = stream.into_split();
+
+    Ok((
+        MessageReader {
+            peer,
+            lines: BufReader::new(reader).lines(),
+        },
+        MessageWriter { writer },
+    ))
+}
+
+/// Waits for the workers, splits the users between them, starts them all at
+/// once then merges the stats they report back
+pub async fn run_coordinator(
+    config: &Config,
+    stats: &StatsCollector,
+    limits: &RunLimits,
+    seed: u64,
+    bind: &str,
+    worker_count: usize,
+    simulation: Simulation,
+) -> Result<()> {
+    let Some(target) = config.multi_target.as_ref() else {
+        return Err(anyhow!("Multi target config must be present."));
+    };
+    if !target.stages.is_empty() {
+        return Err(anyhow!(
+            "Load stages are not supported in distributed runs."
+        ));
+    }
+    if worker_count == 0 || target.users.len() < worker_count {
+        return Err(anyhow!("Every worker needs at least one user."));
+    }
+
+    let listener = TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("Unable to listen on {}", bind))?;
+    info!("Waiting for {} workers on {}", worker_count, bind);
+
+    let mut readers: Vec<MessageReader> = Vec::new();
+    let mut writers: Vec<MessageWriter> = Vec::new();
+    while readers.len() < worker_count {
+        let stream = tokio::select! {
+            accepted = listener.accept() => accepted?.0,
+            _ = limits.shutdown.requested() => return Ok(()),
+        };
+        let (mut reader, writer) = split(stream)?;
+        let Ok(hello) = timeout(HANDSHAKE_TIMEOUT, reader.receive()).await else {
+            warn!(
+                "Rejected worker {}: no hello within {} ms",
+                reader.peer,
+                HANDSHAKE_TIMEOUT.as_millis()
+            );
+            continue;
+        };
+        match hello {
+            Ok(Message::Hello { version }) if version == PROTOCOL_VERSION => {
+                info!(
+                    "Worker {} connected ({}/{})",
+                    reader.peer,
+                    readers.len() + 1,
+                    worker_count
+                );
+                readers.push(reader);
+                writers.push(writer);
+            }
+            Ok(Message::Hello { version }) => {
+                warn!(
+                    "Rejected worker {} running version {}",
+                    reader.peer, version
+                );
+            }
+            Ok(message) => warn!("Unexpected message from {}: {:?}", reader.peer, message),
+            Err(err) => warn!("Rejected worker {}: {}", reader.peer, err),
+        }
+    }
+
+    // Contiguous chunks of about the same size, workers get consecutive seeds
+    // so that their users do not make the same choices. The duration is
+    // counted by the workers from `Start`
+    let duration_ms = limits.duration.map(|duration| duration.as_millis() as u64);
+    let users = &target.users;
+    for (index, writer) in writers.iter_mut().enumerate() {
+        let start = index * users.len() / worker_count;
+        let end = (index + 1) * users.len() / worker_count;
+        let message = Message::Assign {
+            simulation,
+            usernames: users[start..end]
+                .iter()
+                .map(|user| user.username.clone())
+                .collect(),
+            seed: seed.wrapping_add(index as u64),
+            duration_ms,
+            iterations: limits.iterations,
+        };
+        writer.send(&message).await?;
+    }
+
+    for reader in readers.iter_mut() {
+        match reader.receive().await? {
+            Message::Ready => {}
+            Message::Failed { message } => {
+                return Err(anyhow!("Worker {} failed: {}", reader.peer, message));
+            }
+            message => {
+                return Err(anyhow!(
+                    "Unexpected message from {}: {:?}",
+                    reader.peer,
+                    message
+                ));
+            }
+        }
+    }
+    for writer in writers.iter_mut() {
+        writer.send(&Message::Start).await?;
+    }
+    // Waiting for the workers is not part of the run
+    stats.reset_timer();
+    info!("Started {} workers, press Ctrl-C to stop", worker_count);
+
+    let mut reports = JoinSet::new();
+    for mut reader in readers {
+        reports.spawn(async move {
+            let message = reader.receive().await;
+            (reader.peer, message)
+        });
+    }
+
+    // Shutting down the coordinator stops the workers, they still report.
+    // Rates cover the longest worker run, the coordinator may see the last
+    // report well after it ended
+    let mut stopping = false;
+    let mut window = Duration::ZERO;
+    loop {
+        tokio::select! {
+            join_res = reports.join_next() => match join_res {
+                Some(Ok((peer, Ok(Message::Report { stats: data, elapsed_ms })))) => {
+                    info!("Report received from {}", peer);
+                    stats.merge(&data);
+                    window = window.max(Duration::from_millis(elapsed_ms));
+                    stats.set_window(window);
+                }
+                Some(Ok((peer, Ok(Message::Failed { message })))) => {
+                    error!("Worker {} failed: {}", peer, message);
+                }
+                Some(Ok((peer, Ok(message)))) => {
+                    warn!("Unexpected message from {}: {:?}", peer, message);
+                }
+                Some(Ok((peer, Err(err)))) => error!("Lost worker {}: {}", peer, err),
+                Some(Err(err)) => error!("Error: {:?}", err),
+                None => break,
+            },
+            _ = limits.shutdown.requested(), if !stopping => {
+                stopping = true;
+                for writer in writers.iter_mut() {
+                    if let Err(err) = writer.send(&Message::Stop).await {
+                        warn!("Unable to stop a worker: {}", err);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Connects to the coordinator, runs the users it hands out and sends back
+/// the stats once done
+pub async fn run_worker(config: &Config, clients: &ClientFactory, coordinator: &str) -> Result<()> {
+    let stream = connect(clients, coordinator).await?;
+    let (mut reader, mut writer) = split(stream)?;
+    writer
+        .send(&Message::Hello {
+            version: PROTOCOL_VERSION.to_string(),
+        })
+        .await?;
+
+    let Message::Assign {
+        simulation,
+        usernames,
+        seed,
+        duration_ms,
+        iterations,
+    } = reader.receive().await?
+    else {
+        return Err(anyhow!("Expected users from the coordinator."));
+    };
+
+    let config = match assigned_config(config, &usernames) {
+        Ok(config) => config,
+        Err(err) => {
+            let message = err.to_string();
+            writer.send(&Message::Failed { message }).await?;
+            return Err(err);
+        }
+    };
+    writer.send(&Message::Ready).await?;
+
+    match reader.receive().await? {
+        Message::Start => {}
+        Message::Stop => return Ok(()),
+        message => return Err(anyhow!("Unexpected message: {:?}", message)),
+    }
+    info!("Running {} users for {}", usernames.len(), coordinator);
+    let timer = Instant::now();
+    clients.stats().reset_timer();
+
+    // A stop or a lost coordinator works like a local shutdown signal
+    let shutdown = clients.shutdown().clone();
+    let stop = tokio::spawn(async move {
+        match reader.receive().await {
+            Ok(Message::Stop) => info!("Stopped by the coordinator"),
+            Ok(message) => warn!("Unexpected message: {:?}", message),
+            Err(err) => warn!("Lost the coordinator: {}", err),
+        }
+        shutdown.request();
+    });
+
+    let limits = RunLimits::new(
+        duration_ms.map(Duration::from_millis),
+        iterations,
+        clients.shutdown(),
+    );
+    let result = match simulation {
+        Simulation::UsersReadWrite => run_users_read_write(&config, clients, &limits, seed).await,
+        Simulation::UsersReadonly => run_users_readonly(&config, clients, &limits, seed).await,
+    };
+    stop.abort();
+
+    let message = match result.as_ref() {
+        Ok(()) => Message::Report {
+            stats: clients.stats().export(),
+            elapsed_ms: timer.elapsed().as_millis() as u64,
+        },
+        Err(err) => Message::Failed {
+            message: err.to_string(),
+        },
+    };
+    writer.send(&message).await?;
+
+    result
+}
+
+async fn connect(clients: &ClientFactory, coordinator: &str) -> Result<TcpStream> {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match TcpStream::connect(coordinator).await {
+            Ok(stream) => return Ok(stream),
+            Err(err) if attempts < CONNECT_ATTEMPTS && !clients.shutdown().is_requested() => {
+                info!("Waiting for the coordinator at {}: {}", coordinator, err);
+                sleep(Duration::from_secs(1)).await;
+            }
+            Err(err) => {
+                return Err(anyhow!(
+                    "Unable to connect to the coordinator at {}. Error: {}",
+                    coordinator,
+                    err
+                ));
+            }
+        }
+    }
+}
+
+/// Config of the worker narrowed down to the assigned users
+fn assigned_config(config: &Config, usernames: &[String]) -> Result<Config> {
+    let Some(target) = config.multi_target.as_ref() else {
+        return Err(anyhow!("Multi target config must be present."));
+    };
+
+    let mut users: Vec<Credential> = Vec::new();
+    for username in usernames.iter() {
+        let Some(user) = target.users.iter().find(|user| &user.username == username) else {
+            return Err(anyhow!("Missing credentials of {}.", username));
+        };
+        users.push(user.clone());
+    }
+
+    let mut config = config.clone();
+    if let Some(target) = config.multi_target.as_mut() {
+        target.users = users;
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_lines() {
+        let message = Message::Assign {
+            simulation: Simulation::UsersReadonly,
+            usernames: vec!["alice".to_string()],
+            seed: 7,
+            duration_ms: None,
+            iterations: Some(2),
+        };
+        let line = serde_json::to_string(&message).unwrap();
+        assert!(line.starts_with(r#"{"type":"assign","simulation":"users-readonly""#));
+
+        let Message::Assign { usernames, .. } = serde_json::from_str(&line).unwrap() else {
+            panic!("Expected an assign message");
+        };
+        assert_eq!(usernames, vec!["alice".to_string()]);
+    }
+}
@@ -1 +1,199 @@
+use reqwest::{Method, StatusCode};
+use std::fmt;
+use std::time::Duration;
+
 pub type Result<T> = anyhow::Result<T>;
+
+/// Result of a single API call
+pub type ApiResult<T> = std::result::Result<T, ApiError>;
+
+// Keep response bodies short, some error pages are huge
+const BODY_EXCERPT_LENGTH: usize = 500;
+
+/// Which request failed and how long it took
+#[derive(Debug, Clone)]
+pub struct RequestInfo {
+    /// Logical endpoint name, ie: `issues.fetch`
+    pub endpoint: String,
+    pub method: Method,
+    pub url: String,
+    /// Only present when a response arrived
+    pub elapsed: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// Server responded with a non-success status
+    Status {
+        request: RequestInfo,
+        status: StatusCode,
+        body: String,
+    },
+    /// No usable response, ie: connect errors and timeouts
+    Network {
+        request: RequestInfo,
+        class: &'static str,
+        message: String,
+    },
+    /// Response body does not match the expected model
+    Decode {
+        request: RequestInfo,
+        message: String,
+        body: String,
+    },
+    /// Never sent because the run is shutting down
+    Cancelled { request: RequestInfo },
+}
+
+impl ApiError {
+    pub fn status(request: RequestInfo, status: StatusCode, body: &str) -> Self {
+        ApiError::Status {
+            request,
+            status,
+            body: excerpt(body),
+        }
+    }
+
+    pub fn network(request: RequestInfo, class: &'static str, message: String) -> Self {
+        ApiError::Network {
+            request,
+            class,
+            message,
+        }
+    }
+
+    pub fn decode(request: RequestInfo, message: String, body: &str) -> Self {
+        ApiError::Decode {
+            request,
+            message,
+            body: excerpt(body),
+        }
+    }
+
+    pub fn cancelled(request: RequestInfo) -> Self {
+        ApiError::Cancelled { request }
+    }
+
+    pub fn request(&self) -> &RequestInfo {
+        match self {
+            ApiError::Status { request, .. }
+            | ApiError::Network { request, .. }
+            | ApiError::Decode { request, .. }
+            | ApiError::Cancelled { request } => request,
+        }
+    }
+
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        self.status_code() == Some(StatusCode::UNAUTHORIZED)
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status_code() == Some(StatusCode::NOT_FOUND)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, ApiError::Cancelled { .. })
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ApiError::Network { class, .. } if class.ends_with("timeout"))
+    }
+
+    /// Error class used in stats and reports, ie: `http_503` or `timeout`
+    pub fn class(&self) -> String {
+        match self {
+            ApiError::Status { status, .. } => format!("http_{}", status.as_u16()),
+            ApiError::Network { class, .. } => class.to_string(),
+            ApiError::Decode { .. } => "decode".to_string(),
+            ApiError::Cancelled { .. } => "cancelled".to_string(),
+        }
+    }
+
+    /// Details worth keeping as the error sample of its class
+    pub fn sample(&self) -> String {
+        match self {
+            ApiError::Status { body, .. } => body.clone(),
+            ApiError::Network { message, .. } => message.clone(),
+            ApiError::Decode { message, body, .. } => format!("{}, body: {}", message, body),
+            ApiError::Cancelled { .. } => String::new(),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let request = self.request();
+        match self {
+            ApiError::Status { status, .. } => write!(
+                f,
+                "Unable to call {} ({} {}). Error: {}",
+                request.endpoint, request.method, request.url, status
+            ),
+            ApiError::Network { message, .. } => write!(
+                f,
+                "Unable to call {} ({} {}). Error: {}",
+                request.endpoint, request.method, request.url, message
+            ),
+            ApiError::Decode { message, .. } => write!(
+                f,
+                "Unable to decode {} response. Error: {}",
+                request.endpoint, message
+            ),
+            ApiError::Cancelled { .. } => write!(
+                f,
+                "Skipped {} ({} {}), shutting down",
+                request.endpoint, request.method, request.url
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+fn excerpt(body: &str) -> String {
+    body.chars().take(BODY_EXCERPT_LENGTH).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_decisions() {
+        let request = RequestInfo {
+            endpoint: "issues.fetch".to_string(),
+            method: Method::GET,
+            url: "https://example.com/api/projects/1/issues/2".to_string(),
+            elapsed: Some(Duration::from_millis(20)),
+        };
+
+        let body = "x".repeat(600);
+        let not_found = ApiError::status(request.clone(), StatusCode::NOT_FOUND, &body);
+        assert!(not_found.is_not_found());
+        assert!(!not_found.is_unauthorized());
+        assert_eq!(not_found.class(), "http_404");
+        assert_eq!(not_found.sample().len(), BODY_EXCERPT_LENGTH);
+        assert!(
+            not_found
+                .to_string()
+                .ends_with("issues/2). Error: 404 Not Found")
+        );
+
+        let timeout = ApiError::network(request.clone(), "read_timeout", "No response".to_string());
+        assert!(timeout.is_timeout());
+        assert_eq!(timeout.status_code(), None);
+        assert_eq!(timeout.class(), "read_timeout");
+
+        let cancelled = ApiError::cancelled(request);
+        assert!(cancelled.is_cancelled());
+        assert!(!cancelled.is_timeout());
+        assert_eq!(cancelled.class(), "cancelled");
+    }
+}
@@ -1,18 +1,36 @@
+use chrono::Utc;
 use clap::Parser;
 use config::Args;
 use config::Commands;
 use config::Config;
+use distributed::{REPORT_GRACE, run_coordinator, run_worker};
+use progress::spawn_progress_reporter;
+use report::{ReportFormat, RunReport};
+use shutdown::Shutdown;
+use stats::StatsCollector;
 use std::process;
+use std::time::Instant;
+use tasks::client::ClientFactory;
+use tasks::runner::RunLimits;
 use tasks::runner::run_crawl_all_issues;
 use tasks::runner::run_crawl_issues;
 use tasks::runner::run_create_issues;
 use tasks::runner::run_create_seed_project;
+use tasks::runner::run_users_read_write;
+use tasks::runner::run_users_readonly;
+use tokio::time::sleep;
+use tracing::{error, info, warn};
 
 use crate::error::Result;
 
 pub mod config;
+pub mod distributed;
 pub mod error;
 pub mod models;
+pub mod progress;
+pub mod report;
+pub mod shutdown;
+pub mod stats;
 pub mod tasks;
 pub mod token;
 
@@ -36,40 +54,109 @@ async fn main() {
 }
 
 async fn run_command(args: Args, config: Config) -> Result<()> {
-    match args.command {
-        Commands::CreateSeedProject => {
-            match run_create_seed_project(&config.global, &config.single_target).await {
-                Ok(_) => Ok(()),
-                Err(err) => {
-                    eprintln!("{err}");
-                    process::exit(1);
-                }
-            }
-        }
-        Commands::CreateIssues => {
-            match run_create_issues(&config.global, &config.single_target).await {
-                Ok(_) => Ok(()),
-                Err(err) => {
-                    eprintln!("{err}");
-                    process::exit(1);
-                }
+    let command = args.command.name();
+    let started_at = Utc::now();
+    let timer = Instant::now();
+    let stats = StatsCollector::new();
+    let shutdown = Shutdown::listen();
+    let clients = ClientFactory::new(&config.global, &stats, &shutdown);
+    let limits = RunLimits::new(args.duration, args.iterations, &shutdown);
+    let seed = args.seed.unwrap_or_else(rand::random);
+    info!("Random seed {}, pass --seed {} to replay", seed, seed);
+
+    // The coordinator only gets stats from the workers once they are done
+    let coordinating = matches!(args.command, Commands::Coordinator { .. });
+    let progress = if args.progress_interval.is_zero() || coordinating {
+        None
+    } else {
+        Some(spawn_progress_reporter(
+            stats.clone(),
+            args.progress_interval,
+        ))
+    };
+
+    let run = async {
+        match &args.command {
+            Commands::CreateSeedProject => {
+                run_create_seed_project(&config.global, &config.single_target, &clients, seed).await
             }
-        }
-        Commands::CrawlAllIssues => match run_crawl_all_issues(config).await {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                eprintln!("{err}");
-                process::exit(1);
+            Commands::CreateIssues => {
+                run_create_issues(&config.global, &config.single_target, &clients, seed).await
             }
-        },
-        Commands::CrawlIssues => match run_crawl_issues(config).await {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                eprintln!("{err}");
-                process::exit(1);
+            Commands::CrawlAllIssues => run_crawl_all_issues(&config, &clients, &limits).await,
+            Commands::CrawlIssues => run_crawl_issues(&config, &clients, &limits).await,
+            Commands::UsersReadonly => run_users_readonly(&config, &clients, &limits, seed).await,
+            Commands::UsersReadWrite => {
+                run_users_read_write(&config, &clients, &limits, seed).await
             }
-        },
-        Commands::UsersReadonly => Ok(()),
-        Commands::UsersReadWrite => Ok(()),
+            Commands::Coordinator {
+                bind,
+                workers,
+                simulation,
+            } => run_coordinator(&config, &stats, &limits, seed, bind, *workers, *simulation).await,
+            Commands::Worker { coordinator } => run_worker(&config, &clients, coordinator).await,
+        }
+    };
+
+    // Requests in flight get some time to finish after a shutdown signal,
+    // whatever is left after that is dropped. The coordinator outlasts the
+    // grace of its workers to get their reports
+    let grace = if coordinating {
+        args.shutdown_grace + REPORT_GRACE
+    } else {
+        args.shutdown_grace
+    };
+    let result = tokio::select! {
+        result = run => result,
+        _ = async {
+            shutdown.requested().await;
+            sleep(grace).await;
+        } => {
+            warn!(
+                "Requests still in flight after {}, stopping anyway",
+                humantime::format_duration(grace)
+            );
+            Ok(())
+        }
+    };
+    let interrupted = shutdown.is_interrupted();
+
+    if let Some(handle) = progress {
+        handle.abort();
+    }
+
+    // Errors caused by the interruption should not cost the partial report,
+    // other errors still get the summary and report of what ran before exiting
+    let failed = match result {
+        Err(err) if interrupted => {
+            error!("Error: {}", err);
+            false
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            true
+        }
+        Ok(()) => false,
+    };
+
+    if interrupted {
+        println!();
+        println!("Run interrupted, stats cover the partial run only");
+    }
+    stats.print_summary(timer.elapsed().as_millis());
+
+    if let Some(path) = args.report.as_ref() {
+        let format = args
+            .report_format
+            .unwrap_or_else(|| ReportFormat::from_path(path));
+        let report = RunReport::new(command, &config, seed, started_at, interrupted, &stats);
+        report.write(path, format)?;
+        info!("Report written to {}", path.display());
     }
+
+    if failed {
+        process::exit(1);
+    }
+
+    Ok(())
 }
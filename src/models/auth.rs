@@ -19,10 +19,3 @@ pub struct AuthPayload {
     pub password: String,
     pub captcha_token: String,
 }
-
-#[derive(Debug, Clone)]
-pub struct AuthContext {
-    pub api_url: String,
-    pub token: String,
-    pub user: User,
-}
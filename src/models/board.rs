@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use super::{issue::Issue, sprint::Sprint};
+
+/// Issues of the active sprint, columns follow the project issue statuses
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Board {
+    pub sprint: Sprint,
+    pub issues: Vec<Issue>,
+}
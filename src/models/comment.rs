@@ -10,3 +10,17 @@ pub struct Comment {
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateCommentBody {
+    pub body: String,
+    pub body_data: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCommentBody {
+    pub body: String,
+    pub body_data: Option<String>,
+}
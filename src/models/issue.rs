@@ -8,31 +8,53 @@ pub struct Issue {
     pub project_id: String,
     pub epic_id: Option<String>,
     pub parent_id: Option<String>,
+    pub sprint_id: Option<String>,
+    pub assignee_id: Option<String>,
     pub r#type: String,
     pub title: String,
     pub description: Option<String>,
     pub estimate: Option<u32>,
     pub estimate_type: Option<String>,
     pub labels: Option<Vec<String>>,
+    pub status: Option<String>,
+    pub is_follower: Option<bool>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Only the fields that are set are sent in updates
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct IssuePartial {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub project_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub epic_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub estimate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub estimate_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub labels: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<String>,
 }
 
@@ -51,3 +73,16 @@ pub struct CreateIssueBody {
     pub status: Option<String>,
     pub labels: Vec<String>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateIssueStatusBody {
+    pub status: String,
+}
+
+/// No sprint moves the issue back to the backlog
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateIssueSprintBody {
+    pub sprint_id: Option<String>,
+}
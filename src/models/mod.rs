@@ -4,6 +4,7 @@ pub mod actor;
 pub mod auth;
 pub mod authz;
 pub mod avatar;
+pub mod board;
 pub mod channel;
 pub mod comment;
 pub mod file;
@@ -53,21 +53,21 @@ pub struct CreateProjectBody {
     pub backlog_on_board: Option<bool>,
 }
 
-pub fn generate_project_key(title: &str) -> String {
+pub fn generate_project_key<R: Rng + ?Sized>(rng: &mut R, title: &str) -> String {
     let mut keys: Vec<String> = Vec::new();
     let chunks: Vec<&str> = title.split_whitespace().collect();
     for chunk in chunks.into_iter() {
         // Get first char
         let c = chunk.chars().next();
-        if let Some(c) = c {
-            if c.is_ascii_alphabetic() {
-                keys.push(c.to_ascii_uppercase().to_string());
-            }
+        if let Some(c) = c
+            && c.is_ascii_alphabetic()
+        {
+            keys.push(c.to_ascii_uppercase().to_string());
         }
     }
 
     // Add random numbers from 0-99
-    let num: u32 = rand::rng().random_range(1..=99);
+    let num: u32 = rng.random_range(1..=99);
     keys.push(num.to_string());
 
     keys.join("")
@@ -76,12 +76,18 @@ pub fn generate_project_key(title: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
 
     #[test]
     fn test_generate_project_key() {
         let title = "Awesome Test Project";
-        let key = generate_project_key(title);
+        let key = generate_project_key(&mut StdRng::seed_from_u64(7), title);
         assert!(key.starts_with("ATP"));
         assert!(key.len() > 3);
+
+        // Same seed, same key
+        let replayed = generate_project_key(&mut StdRng::seed_from_u64(7), title);
+        assert_eq!(key, replayed);
     }
 }
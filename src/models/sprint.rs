@@ -13,3 +13,29 @@ pub struct Sprint {
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
+
+impl Sprint {
+    pub fn is_active(&self) -> bool {
+        self.status.as_deref() == Some("active")
+    }
+
+    /// Created but neither started nor completed yet
+    pub fn is_planned(&self) -> bool {
+        self.status.as_deref() == Some("planned")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSprintBody {
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// Dates are formatted as `YYYY-MM-DD`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartSprintBody {
+    pub start_date: String,
+    pub end_date: String,
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::info;
+
+use crate::stats::StatsCollector;
+
+/// Prints rolling throughput, in-flight requests, error rate and p95 on every tick
+/// until the returned handle is aborted
+pub fn spawn_progress_reporter(stats: StatsCollector, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = interval(every);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // First tick completes immediately
+        ticker.tick().await;
+        stats.take_interval();
+
+        loop {
+            ticker.tick().await;
+            let progress = stats.take_interval();
+            info!(
+                "Progress: {:.2} rps, {} in flight, {:.2}% errors, p95 {} ms, {} requests",
+                progress.requests_per_second,
+                progress.in_flight,
+                progress.error_rate,
+                progress.p95,
+                progress.total_requests
+            );
+        }
+    })
+}
@@ -0,0 +1,294 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::{
+    config::Config,
+    error::Result,
+    stats::{StatsCollector, StatsSnapshot, StatsSummary},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    /// Picks the format from the file extension, defaults to JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => ReportFormat::Csv,
+            _ => ReportFormat::Json,
+        }
+    }
+}
+
+/// Config values worth keeping in a report, never includes secrets
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigSummary {
+    pub api_url: String,
+    pub single_target: Option<SingleTargetSummary>,
+    pub multi_target: Option<MultiTargetSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SingleTargetSummary {
+    pub username: String,
+    pub project_id: String,
+    pub issue_count: u32,
+    pub issue_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MultiTargetSummary {
+    pub users: Vec<String>,
+    pub issue_count: u32,
+    pub issue_type: Option<String>,
+}
+
+impl From<&Config> for ConfigSummary {
+    fn from(config: &Config) -> Self {
+        ConfigSummary {
+            api_url: config.global.api_url.clone(),
+            single_target: config
+                .single_target
+                .as_ref()
+                .map(|target| SingleTargetSummary {
+                    username: target.username.clone(),
+                    project_id: target.project_id.clone(),
+                    issue_count: target.issue_count,
+                    issue_type: target.issue_type.clone(),
+                }),
+            multi_target: config
+                .multi_target
+                .as_ref()
+                .map(|target| MultiTargetSummary {
+                    users: target.users.iter().map(|u| u.username.clone()).collect(),
+                    issue_count: target.issue_count,
+                    issue_type: target.issue_type.clone(),
+                }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub command: String,
+    pub config: ConfigSummary,
+    /// Replays the same random choices with `--seed`
+    pub seed: u64,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub run_duration_ms: u128,
+    /// Stopped by Ctrl-C or SIGTERM, the stats only cover part of the run
+    pub interrupted: bool,
+    #[serde(flatten)]
+    pub stats: StatsSnapshot,
+}
+
+impl RunReport {
+    pub fn new(
+        command: &str,
+        config: &Config,
+        seed: u64,
+        started_at: DateTime<Utc>,
+        interrupted: bool,
+        stats: &StatsCollector,
+    ) -> Self {
+        let finished_at = Utc::now();
+        let run_duration_ms = (finished_at - started_at).num_milliseconds().max(0) as u128;
+
+        RunReport {
+            command: command.to_string(),
+            config: ConfigSummary::from(config),
+            seed,
+            started_at,
+            finished_at,
+            run_duration_ms,
+            interrupted,
+            stats: stats.snapshot(),
+        }
+    }
+
+    pub fn write(&self, path: &Path, format: ReportFormat) -> Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Unable to create report file {}", path.display()))?;
+
+        match format {
+            ReportFormat::Json => serde_json::to_writer_pretty(file, self)?,
+            ReportFormat::Csv => self.write_csv(file)?,
+        }
+
+        Ok(())
+    }
+
+    fn write_csv<W: Write>(&self, output: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(output);
+        writer.serialize(CsvRow::new(self, "all", &self.stats.overall))?;
+        for (endpoint, summary) in self.stats.endpoints.iter() {
+            writer.serialize(CsvRow::new(self, endpoint.as_str(), summary))?;
+        }
+        for stage in self.stats.stages.iter() {
+            let scope = format!("stage:{}", stage.name);
+            writer.serialize(CsvRow::new(self, &scope, &stage.stats))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Flat CSV row, one per scope so that spreadsheets can pivot on it
+#[derive(Debug, Serialize)]
+struct CsvRow<'a> {
+    command: &'a str,
+    api_url: &'a str,
+    seed: u64,
+    started_at: String,
+    finished_at: String,
+    run_duration_ms: u128,
+    interrupted: bool,
+    scope: &'a str,
+    total_requests: u64,
+    succeed: u64,
+    failed: u64,
+    retries: u64,
+    success_rate: f64,
+    error_rate: f64,
+    requests_per_second: f64,
+    min_ms: u64,
+    mean_ms: f64,
+    stdev_ms: f64,
+    max_ms: u64,
+    p50_ms: u64,
+    p75_ms: u64,
+    p90_ms: u64,
+    p95_ms: u64,
+    p99_ms: u64,
+    p999_ms: u64,
+    errors: String,
+}
+
+impl<'a> CsvRow<'a> {
+    fn new(report: &'a RunReport, scope: &'a str, summary: &StatsSummary) -> Self {
+        let errors: Vec<String> = summary
+            .errors
+            .iter()
+            .map(|(class, error)| format!("{}={}", class, error.count))
+            .collect();
+
+        CsvRow {
+            command: report.command.as_str(),
+            api_url: report.config.api_url.as_str(),
+            seed: report.seed,
+            started_at: report.started_at.to_rfc3339(),
+            finished_at: report.finished_at.to_rfc3339(),
+            run_duration_ms: report.run_duration_ms,
+            interrupted: report.interrupted,
+            scope,
+            total_requests: summary.total_requests,
+            succeed: summary.succeed,
+            failed: summary.failed,
+            retries: summary.retries,
+            success_rate: summary.success_rate,
+            error_rate: summary.error_rate,
+            requests_per_second: summary.requests_per_second,
+            min_ms: summary.latency.min,
+            mean_ms: summary.latency.mean,
+            stdev_ms: summary.latency.stdev,
+            max_ms: summary.latency.max,
+            p50_ms: summary.latency.p50,
+            p75_ms: summary.latency.p75,
+            p90_ms: summary.latency.p90,
+            p95_ms: summary.latency.p95,
+            p99_ms: summary.latency.p99,
+            p999_ms: summary.latency.p999,
+            errors: errors.join(";"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> RunReport {
+        let config: Config = toml::from_str(
+            r#"
+            [global]
+            api_url = "http://localhost/api"
+            jwt_secret = "secret"
+
+            [multi_target]
+            users = [{ username = "alice", password = "secret" }]
+            issue_count = 5
+            "#,
+        )
+        .unwrap();
+
+        let stats = StatsCollector::new();
+        stats.start_stage("ramp");
+        stats.record("issues.fetch", 100);
+        stats.record_failure("projects.list", None, "timeout", "slow");
+        stats.finish_stage();
+
+        RunReport::new("users-read-write", &config, 42, Utc::now(), false, &stats)
+    }
+
+    #[test]
+    fn test_csv_rows() {
+        let report = sample_report();
+        let mut output: Vec<u8> = Vec::new();
+        report.write_csv(&mut output).unwrap();
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let headers = reader.headers().unwrap().clone();
+        assert_eq!(&headers[0], "command");
+        assert_eq!(&headers[7], "scope");
+        assert_eq!(&headers[headers.len() - 1], "errors");
+
+        let rows: Vec<csv::StringRecord> = reader.records().map(|row| row.unwrap()).collect();
+        let scopes: Vec<&str> = rows.iter().map(|row| &row[7]).collect();
+        assert_eq!(
+            scopes,
+            vec!["all", "issues.fetch", "projects.list", "stage:ramp"]
+        );
+        assert_eq!(&rows[0][8], "2");
+        assert_eq!(&rows[0][headers.len() - 1], "timeout=1");
+    }
+
+    #[test]
+    fn test_json_keys() {
+        let report = sample_report();
+        let value = serde_json::to_value(&report).unwrap();
+        let json = value.as_object().unwrap();
+
+        // Overall stats sit at the top level next to the run details
+        for key in [
+            "command",
+            "config",
+            "seed",
+            "started_at",
+            "finished_at",
+            "run_duration_ms",
+            "interrupted",
+            "total_requests",
+            "requests_per_second",
+            "latency",
+            "errors",
+            "endpoints",
+            "stages",
+        ] {
+            assert!(json.contains_key(key), "missing {}", key);
+        }
+        assert!(!json.contains_key("overall"));
+        assert_eq!(json["stages"][0]["name"], "ramp");
+        assert_eq!(json["stages"][0]["total_requests"], 2);
+        assert_eq!(json["config"]["multi_target"]["users"][0], "alice");
+        assert!(!value.to_string().contains("\"password\""));
+    }
+}
@@ -0,0 +1,91 @@
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::signal;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Fires once on SIGINT or SIGTERM, running work stops starting new requests
+/// and lets the ones in flight finish. A second signal quits right away.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+    receiver: watch::Receiver<bool>,
+    // Expiring stops the same way but the run is complete
+    interrupted: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    pub fn listen() -> Self {
+        let shutdown = Shutdown::default();
+
+        let signaled = shutdown.clone();
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            info!("Shutting down, waiting for requests in flight, signal again to quit now");
+            signaled.request();
+
+            wait_for_signal().await;
+            warn!("Quitting without waiting");
+            process::exit(130);
+        });
+
+        shutdown
+    }
+
+    /// Same as receiving a signal, ie: when the coordinator stops a worker
+    pub fn request(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+        self.sender.send_replace(true);
+    }
+
+    /// Stops new requests once `--duration` is reached, the ones in flight
+    /// finish and are recorded but the run does not count as interrupted
+    pub fn expire(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Stopped by a signal or the coordinator rather than by the duration
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once shutdown is requested
+    pub async fn requested(&self) {
+        let mut receiver = self.receiver.clone();
+        let _ = receiver.wait_for(|requested| *requested).await;
+    }
+}
+
+/// Only requested by hand, for work that runs without signal handling
+impl Default for Shutdown {
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(false);
+        Shutdown {
+            sender: Arc::new(sender),
+            receiver,
+            interrupted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Unable to listen for SIGTERM");
+        tokio::select! {
+            _ = signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+    }
+}
@@ -0,0 +1,723 @@
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Track latencies from 1 ms up to 1 hour with 3 significant digits
+const HISTOGRAM_MAX_MS: u64 = 3_600_000;
+const HISTOGRAM_SIGFIG: u8 = 3;
+
+// Keep error samples short enough for console output and reports
+const ERROR_SAMPLE_LENGTH: usize = 500;
+
+/// Request outcomes and latency samples for a single bucket
+#[derive(Clone)]
+pub struct LatencyStats {
+    histogram: Histogram<u64>,
+    total: u64,
+    failed: u64,
+    // Attempts that failed but were retried, not part of the totals
+    retries: u64,
+    errors: BTreeMap<String, ErrorSummary>,
+}
+
+/// Failures of a single error class with the first error body seen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorSummary {
+    pub count: u64,
+    pub sample: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub stdev: f64,
+    pub p50: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub p999: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsSummary {
+    pub total_requests: u64,
+    pub succeed: u64,
+    pub failed: u64,
+    pub retries: u64,
+    pub success_rate: f64,
+    pub error_rate: f64,
+    pub requests_per_second: f64,
+    pub latency: LatencySummary,
+    pub errors: BTreeMap<String, ErrorSummary>,
+}
+
+/// Stats of a single load stage, in the order the stages ran
+#[derive(Debug, Clone, Serialize)]
+pub struct StageSummary {
+    pub name: String,
+    pub duration_ms: u128,
+    #[serde(flatten)]
+    pub stats: StatsSummary,
+}
+
+/// Raw counts of a bucket, lets stats recorded by another process be merged
+/// without losing percentile precision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyData {
+    /// Recorded values with their counts
+    pub samples: Vec<(u64, u64)>,
+    pub total: u64,
+    pub failed: u64,
+    pub retries: u64,
+    pub errors: BTreeMap<String, ErrorSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsData {
+    pub overall: LatencyData,
+    pub endpoints: BTreeMap<String, LatencyData>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsSnapshot {
+    #[serde(flatten)]
+    pub overall: StatsSummary,
+    pub endpoints: BTreeMap<String, StatsSummary>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<StageSummary>,
+}
+
+impl LatencyStats {
+    pub fn new() -> Self {
+        let histogram = Histogram::new_with_max(HISTOGRAM_MAX_MS, HISTOGRAM_SIGFIG)
+            .expect("Histogram bounds must be valid");
+
+        Self {
+            histogram,
+            total: 0,
+            failed: 0,
+            retries: 0,
+            errors: BTreeMap::new(),
+        }
+    }
+
+    pub fn record_sample(&mut self, duration: u128) {
+        let value = u64::try_from(duration).unwrap_or(HISTOGRAM_MAX_MS);
+        self.histogram
+            .saturating_record(value.min(HISTOGRAM_MAX_MS));
+        self.total += 1;
+    }
+
+    /// Records a failed request, duration is only present when a response arrived
+    pub fn record_failure(&mut self, duration: Option<u128>, class: &str, sample: &str) {
+        match duration {
+            Some(value) => self.record_sample(value),
+            None => self.total += 1,
+        }
+        self.failed += 1;
+
+        let error = self
+            .errors
+            .entry(class.to_string())
+            .or_insert_with(|| ErrorSummary {
+                count: 0,
+                sample: sample.chars().take(ERROR_SAMPLE_LENGTH).collect(),
+            });
+        error.count += 1;
+    }
+
+    pub fn record_retry(&mut self) {
+        self.retries += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn succeed(&self) -> u64 {
+        self.total - self.failed
+    }
+
+    pub fn failed(&self) -> u64 {
+        self.failed
+    }
+
+    pub fn retries(&self) -> u64 {
+        self.retries
+    }
+
+    pub fn errors(&self) -> &BTreeMap<String, ErrorSummary> {
+        &self.errors
+    }
+
+    pub fn success_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.succeed() as f64 / self.total as f64) * 100.0
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.failed as f64 / self.total as f64) * 100.0
+    }
+
+    pub fn latency(&self) -> LatencySummary {
+        let h = &self.histogram;
+        LatencySummary {
+            min: h.min(),
+            max: h.max(),
+            mean: h.mean(),
+            stdev: h.stdev(),
+            p50: h.value_at_quantile(0.50),
+            p75: h.value_at_quantile(0.75),
+            p90: h.value_at_quantile(0.90),
+            p95: h.value_at_quantile(0.95),
+            p99: h.value_at_quantile(0.99),
+            p999: h.value_at_quantile(0.999),
+        }
+    }
+
+    pub fn export(&self) -> LatencyData {
+        LatencyData {
+            samples: self
+                .histogram
+                .iter_recorded()
+                .map(|value| (value.value_iterated_to(), value.count_at_value()))
+                .collect(),
+            total: self.total,
+            failed: self.failed,
+            retries: self.retries,
+            errors: self.errors.clone(),
+        }
+    }
+
+    /// Adds exported counts, the first error sample of each class is kept
+    pub fn merge(&mut self, data: &LatencyData) {
+        for (value, count) in data.samples.iter() {
+            self.histogram.saturating_record_n(*value, *count);
+        }
+        self.total += data.total;
+        self.failed += data.failed;
+        self.retries += data.retries;
+
+        for (class, error) in data.errors.iter() {
+            self.errors
+                .entry(class.clone())
+                .and_modify(|existing| existing.count += error.count)
+                .or_insert_with(|| error.clone());
+        }
+    }
+
+    pub fn summary(&self, elapsed_secs: f64) -> StatsSummary {
+        let requests_per_second = if elapsed_secs > 0.0 {
+            self.total as f64 / elapsed_secs
+        } else {
+            0.0
+        };
+
+        StatsSummary {
+            total_requests: self.total,
+            succeed: self.succeed(),
+            failed: self.failed,
+            retries: self.retries,
+            success_rate: self.success_rate(),
+            error_rate: self.error_rate(),
+            requests_per_second,
+            latency: self.latency(),
+            errors: self.errors.clone(),
+        }
+    }
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct StageStats {
+    name: String,
+    stats: LatencyStats,
+    started: Instant,
+    finished: Option<Instant>,
+}
+
+struct CollectorState {
+    overall: LatencyStats,
+    endpoints: BTreeMap<String, LatencyStats>,
+    // Only the last stage is running, samples are recorded into it
+    stages: Vec<StageStats>,
+    // Samples since the last progress tick
+    interval: LatencyStats,
+    interval_timer: Instant,
+    timer: Instant,
+    // Replaces the time since the timer started, ie: the run of the workers
+    window: Option<Duration>,
+}
+
+/// Shared stats collector, cheap to clone and safe to record into from any task
+#[derive(Clone)]
+pub struct StatsCollector {
+    state: Arc<Mutex<CollectorState>>,
+    in_flight: Arc<AtomicU64>,
+}
+
+/// Rolling numbers since the previous progress tick
+#[derive(Debug, Clone)]
+pub struct IntervalSummary {
+    pub total_requests: u64,
+    pub in_flight: u64,
+    pub requests_per_second: f64,
+    pub error_rate: f64,
+    pub p95: u64,
+}
+
+/// Counts a request as in flight until dropped
+pub struct InFlightGuard {
+    in_flight: Arc<AtomicU64>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl CollectorState {
+    fn running_stage(&mut self) -> Option<&mut LatencyStats> {
+        self.stages
+            .last_mut()
+            .filter(|stage| stage.finished.is_none())
+            .map(|stage| &mut stage.stats)
+    }
+}
+
+impl StatsCollector {
+    pub fn new() -> Self {
+        let state = CollectorState {
+            overall: LatencyStats::new(),
+            endpoints: BTreeMap::new(),
+            stages: Vec::new(),
+            interval: LatencyStats::new(),
+            interval_timer: Instant::now(),
+            timer: Instant::now(),
+            window: None,
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            in_flight: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Rates are counted from now on, ie: once all workers of a distributed
+    /// run are ready
+    pub fn reset_timer(&self) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        state.timer = Instant::now();
+        state.interval_timer = state.timer;
+    }
+
+    /// Rates are computed over this window instead of the time since the timer
+    /// started, ie: the longest run of the workers
+    pub fn set_window(&self, window: Duration) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        state.window = Some(window);
+    }
+
+    /// Marks a request as started, it stays in flight until the guard is dropped
+    pub fn start_request(&self) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+
+    /// Records a successful request under the given endpoint name
+    pub fn record(&self, endpoint: &str, duration: u128) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        state.overall.record_sample(duration);
+        state.interval.record_sample(duration);
+        if let Some(stage) = state.running_stage() {
+            stage.record_sample(duration);
+        }
+        state
+            .endpoints
+            .entry(endpoint.to_string())
+            .or_default()
+            .record_sample(duration);
+    }
+
+    /// Records a failed request under the given endpoint name and error class,
+    /// duration is only present when a response arrived
+    pub fn record_failure(
+        &self,
+        endpoint: &str,
+        duration: Option<u128>,
+        class: &str,
+        sample: &str,
+    ) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        state.overall.record_failure(duration, class, sample);
+        state.interval.record_failure(duration, class, sample);
+        if let Some(stage) = state.running_stage() {
+            stage.record_failure(duration, class, sample);
+        }
+        state
+            .endpoints
+            .entry(endpoint.to_string())
+            .or_default()
+            .record_failure(duration, class, sample);
+    }
+
+    /// Records a failed attempt that is about to be retried
+    pub fn record_retry(&self, endpoint: &str) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        state.overall.record_retry();
+        state.interval.record_retry();
+        if let Some(stage) = state.running_stage() {
+            stage.record_retry();
+        }
+        state
+            .endpoints
+            .entry(endpoint.to_string())
+            .or_default()
+            .record_retry();
+    }
+
+    /// Ends the running stage, if any, and records everything after into a new one
+    pub fn start_stage(&self, name: &str) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        let now = Instant::now();
+        if let Some(stage) = state.stages.last_mut() {
+            stage.finished.get_or_insert(now);
+        }
+        state.stages.push(StageStats {
+            name: name.to_string(),
+            stats: LatencyStats::new(),
+            started: now,
+            finished: None,
+        });
+    }
+
+    pub fn finish_stage(&self) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        if let Some(stage) = state.stages.last_mut() {
+            stage.finished.get_or_insert_with(Instant::now);
+        }
+    }
+
+    pub fn export(&self) -> StatsData {
+        let state = self.state.lock().expect("Stats lock poisoned");
+        StatsData {
+            overall: state.overall.export(),
+            endpoints: state
+                .endpoints
+                .iter()
+                .map(|(name, stats)| (name.clone(), stats.export()))
+                .collect(),
+        }
+    }
+
+    /// Adds stats exported by another process, ie: a worker of a distributed run
+    pub fn merge(&self, data: &StatsData) {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        state.overall.merge(&data.overall);
+        for (name, endpoint) in data.endpoints.iter() {
+            state
+                .endpoints
+                .entry(name.clone())
+                .or_default()
+                .merge(endpoint);
+        }
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let state = self.state.lock().expect("Stats lock poisoned");
+        let elapsed = state.window.unwrap_or_else(|| state.timer.elapsed());
+        let elapsed_secs = elapsed.as_secs_f64();
+
+        StatsSnapshot {
+            overall: state.overall.summary(elapsed_secs),
+            endpoints: state
+                .endpoints
+                .iter()
+                .map(|(name, stats)| (name.clone(), stats.summary(elapsed_secs)))
+                .collect(),
+            stages: state
+                .stages
+                .iter()
+                .map(|stage| {
+                    let finished = stage.finished.unwrap_or_else(Instant::now);
+                    let duration = finished.duration_since(stage.started);
+                    StageSummary {
+                        name: stage.name.clone(),
+                        duration_ms: duration.as_millis(),
+                        stats: stage.stats.summary(duration.as_secs_f64()),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Summarizes the samples since the previous call then starts a new interval
+    pub fn take_interval(&self) -> IntervalSummary {
+        let mut state = self.state.lock().expect("Stats lock poisoned");
+        let elapsed_secs = state.interval_timer.elapsed().as_secs_f64();
+        let summary = state.interval.summary(elapsed_secs);
+
+        state.interval = LatencyStats::new();
+        state.interval_timer = Instant::now();
+
+        IntervalSummary {
+            total_requests: state.overall.total(),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            requests_per_second: summary.requests_per_second,
+            error_rate: summary.error_rate,
+            p95: summary.latency.p95,
+        }
+    }
+
+    pub fn print_summary(&self, run_duration: u128) {
+        let snapshot = self.snapshot();
+        let overall = &snapshot.overall;
+        let latency = &overall.latency;
+
+        println!();
+        println!("Total requests: {}", overall.total_requests);
+        println!("Succeed: {}", overall.succeed);
+        println!("Failed: {}", overall.failed);
+        println!("Retries: {}", overall.retries);
+        println!("Success rate: {:.2}%", overall.success_rate);
+        println!("Min: {} ms", latency.min);
+        println!("Avg: {:.2} ms", latency.mean);
+        println!("Std dev: {:.2} ms", latency.stdev);
+        println!("Max: {} ms", latency.max);
+        println!("p50: {} ms", latency.p50);
+        println!("p75: {} ms", latency.p75);
+        println!("p90: {} ms", latency.p90);
+        println!("p95: {} ms", latency.p95);
+        println!("p99: {} ms", latency.p99);
+        println!("p99.9: {} ms", latency.p999);
+        println!("Requests per second: {:.2}", overall.requests_per_second);
+        println!("Run duration: {} ms", run_duration);
+
+        for (class, error) in overall.errors.iter() {
+            println!("Error {}: {}", class, error.count);
+        }
+
+        println!();
+        println!(
+            "{:<28} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "Endpoint", "Count", "Err %", "Retries", "p50", "p90", "p95", "p99", "Max"
+        );
+        for (name, summary) in snapshot.endpoints.iter() {
+            let latency = &summary.latency;
+            println!(
+                "{:<28} {:>8} {:>8.2} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                name,
+                summary.total_requests,
+                summary.error_rate,
+                summary.retries,
+                latency.p50,
+                latency.p90,
+                latency.p95,
+                latency.p99,
+                latency.max
+            );
+        }
+
+        if !snapshot.stages.is_empty() {
+            println!();
+            println!(
+                "{:<28} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                "Stage", "Count", "Err %", "RPS", "p50", "p95", "p99", "Max"
+            );
+            for stage in snapshot.stages.iter() {
+                let summary = &stage.stats;
+                let latency = &summary.latency;
+                println!(
+                    "{:<28} {:>8} {:>8.2} {:>8.2} {:>8} {:>8} {:>8} {:>8}",
+                    stage.name,
+                    summary.total_requests,
+                    summary.error_rate,
+                    summary.requests_per_second,
+                    latency.p50,
+                    latency.p95,
+                    latency.p99,
+                    latency.max
+                );
+            }
+        }
+
+        let failed_endpoints = snapshot
+            .endpoints
+            .iter()
+            .filter(|(_, summary)| !summary.errors.is_empty());
+        for (name, summary) in failed_endpoints {
+            println!();
+            println!("Errors for {}:", name);
+            for (class, error) in summary.errors.iter() {
+                let sample: String = error.sample.chars().take(120).collect();
+                println!("  {} x{}: {}", class, error.count, sample);
+            }
+        }
+    }
+}
+
+impl Default for StatsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let mut stats = LatencyStats::new();
+        for duration in 1..=1000 {
+            stats.record_sample(duration);
+        }
+        stats.record_failure(None, "connect", "Connection refused");
+
+        assert_eq!(stats.total(), 1001);
+        assert_eq!(stats.failed(), 1);
+        assert_eq!(stats.errors().get("connect").unwrap().count, 1);
+
+        let latency = stats.latency();
+        assert_eq!(latency.min, 1);
+        assert_eq!(latency.max, 1000);
+        assert_eq!(latency.p50, 500);
+        assert_eq!(latency.p90, 900);
+        assert_eq!(latency.p99, 990);
+        assert_eq!(latency.p999, 999);
+        assert!(latency.stdev > 288.0 && latency.stdev < 289.0);
+    }
+
+    #[test]
+    fn test_endpoint_breakdown() {
+        let stats = StatsCollector::new();
+        stats.record("issues.fetch", 100);
+        stats.record_failure("issues.fetch", Some(300), "http_503", "first");
+        stats.record_failure("issues.fetch", Some(200), "http_503", "second");
+        stats.record_retry("issues.fetch");
+        stats.record("projects.list", 50);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.overall.total_requests, 4);
+        assert_eq!(snapshot.overall.failed, 2);
+        assert_eq!(snapshot.overall.retries, 1);
+
+        let issues = snapshot.endpoints.get("issues.fetch").unwrap();
+        assert_eq!(issues.total_requests, 3);
+        assert_eq!(issues.failed, 2);
+        assert_eq!(issues.retries, 1);
+        assert_eq!(issues.latency.max, 300);
+
+        let error = issues.errors.get("http_503").unwrap();
+        assert_eq!(error.count, 2);
+        assert_eq!(error.sample, "first");
+
+        let projects = snapshot.endpoints.get("projects.list").unwrap();
+        assert_eq!(projects.total_requests, 1);
+        assert_eq!(projects.error_rate, 0.0);
+    }
+
+    #[test]
+    fn test_interval_resets() {
+        let stats = StatsCollector::new();
+        let guard = stats.start_request();
+        stats.record_failure("issues.fetch", Some(100), "timeout", "");
+
+        let interval = stats.take_interval();
+        assert_eq!(interval.in_flight, 1);
+        assert_eq!(interval.error_rate, 100.0);
+        assert_eq!(interval.p95, 100);
+
+        drop(guard);
+        let interval = stats.take_interval();
+        assert_eq!(interval.in_flight, 0);
+        assert_eq!(interval.total_requests, 1);
+        assert_eq!(interval.error_rate, 0.0);
+    }
+
+    #[test]
+    fn test_stage_breakdown() {
+        let stats = StatsCollector::new();
+        stats.record("issues.fetch", 100);
+        stats.start_stage("ramp-up");
+        stats.record("issues.fetch", 200);
+        stats.start_stage("steady");
+        stats.record_failure("issues.fetch", None, "timeout", "");
+        stats.finish_stage();
+        stats.record("issues.fetch", 300);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.overall.total_requests, 4);
+
+        let names: Vec<&str> = snapshot.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["ramp-up", "steady"]);
+        assert_eq!(snapshot.stages[0].stats.total_requests, 1);
+        assert_eq!(snapshot.stages[0].stats.latency.max, 200);
+        assert_eq!(snapshot.stages[1].stats.total_requests, 1);
+        assert_eq!(snapshot.stages[1].stats.failed, 1);
+    }
+
+    #[test]
+    fn test_merge_exported_stats() {
+        let worker = StatsCollector::new();
+        for duration in 1..=100 {
+            worker.record("issues.fetch", duration);
+        }
+        worker.record_failure("issues.fetch", None, "timeout", "worker");
+        worker.record_retry("issues.fetch");
+
+        let coordinator = StatsCollector::new();
+        coordinator.record("issues.fetch", 1000);
+        coordinator.record_failure("projects.list", None, "timeout", "coordinator");
+
+        let data: StatsData =
+            serde_json::from_str(&serde_json::to_string(&worker.export()).unwrap()).unwrap();
+        coordinator.merge(&data);
+
+        let snapshot = coordinator.snapshot();
+        assert_eq!(snapshot.overall.total_requests, 103);
+        assert_eq!(snapshot.overall.failed, 2);
+        assert_eq!(snapshot.overall.retries, 1);
+        assert_eq!(snapshot.overall.latency.max, 1000);
+
+        let timeouts = snapshot.overall.errors.get("timeout").unwrap();
+        assert_eq!(timeouts.count, 2);
+        assert_eq!(timeouts.sample, "coordinator");
+
+        let issues = snapshot.endpoints.get("issues.fetch").unwrap();
+        assert_eq!(issues.total_requests, 102);
+        assert_eq!(issues.latency.p50, 51);
+    }
+
+    #[test]
+    fn test_rate_window() {
+        let stats = StatsCollector::new();
+        for duration in 1..=100 {
+            stats.record("issues.fetch", duration);
+        }
+        stats.reset_timer();
+        stats.set_window(Duration::from_secs(4));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.overall.requests_per_second, 25.0);
+        assert_eq!(snapshot.endpoints["issues.fetch"].requests_per_second, 25.0);
+    }
+}
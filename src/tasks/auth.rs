@@ -1,37 +1,30 @@
 use anyhow::anyhow;
-use reqwest::Client;
-use tracing::error;
 
 use crate::{
-    error::Result,
+    error::{ApiResult, Result},
     models::{
-        auth::{AuthContext, AuthPayload, AuthSuccess},
+        auth::{AuthPayload, AuthSuccess},
         authz::Authz,
+        user::User,
     },
 };
 
-use super::{JSON_CONTENT_TYPE, USER_AGENT};
+use super::client::{Access, ApiClient, ReadWrite};
 
-pub async fn authenticate(api_url: &str, payload: AuthPayload) -> Result<AuthContext> {
-    let url = format!("{}/auth/token/email", api_url);
-    let post_body = serde_json::to_string(&payload)?;
-
-    let response = Client::new()
-        .post(url)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .body(post_body)
-        .send()
-        .await?;
+/// Logged in user, the access level decides which tasks accept it
+#[derive(Debug, Clone)]
+pub struct AuthContext<A = ReadWrite> {
+    pub client: ApiClient<A>,
+    pub user: User,
+}
 
-    if !response.status().is_success() {
-        let err_status = response.status();
-        let err_data: String = response.text().await?;
-        error!("Error: {}", err_data);
-        return Err(anyhow!("Unable to authenticate. Error: {}", err_status));
-    }
+pub async fn authenticate<A: Access>(
+    client: &ApiClient<A>,
+    payload: AuthPayload,
+) -> Result<AuthContext<A>> {
+    let request = client.login(&payload);
 
-    let auth_data: AuthSuccess = response.json().await?;
+    let auth_data: AuthSuccess = client.send("auth.login", request).await?;
     let Some(token) = auth_data.token else {
         return Err(anyhow!("Unable to authenticate. No token received."));
     };
@@ -39,34 +32,17 @@ pub async fn authenticate(api_url: &str, payload: AuthPayload) -> Result<AuthCon
         return Err(anyhow!("Unable to authenticate. No user data received."));
     };
     Ok(AuthContext {
-        api_url: api_url.to_string(),
-        token,
+        client: client.with_token(token.as_str()),
         user,
     })
 }
 
-pub async fn fetch_project_authz(ctx: &AuthContext, project_id: &str) -> Result<Authz> {
-    let url = format!(
-        "{}/user/authContext/projects/{}",
-        ctx.api_url.as_str(),
-        project_id
-    );
-    let response = Client::new()
-        .get(url)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
+pub async fn fetch_project_authz<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> ApiResult<Authz> {
+    let url = format!("/user/authContext/projects/{}", project_id);
+    let request = ctx.client.get(&url);
 
-    if response.status().is_success() {
-        let authz: Authz = response.json().await?;
-        Ok(authz)
-    } else {
-        Err(anyhow!(
-            "Unable to fetch project permissions {}. Error: {}",
-            project_id,
-            response.status()
-        ))
-    }
+    ctx.client.send("projects.authz", request).await
 }
@@ -0,0 +1,42 @@
+use crate::{
+    error::ApiResult,
+    models::{
+        issue::{Issue, UpdateIssueSprintBody},
+        pagination::PaginationResult,
+    },
+};
+
+use super::{auth::AuthContext, client::Access};
+
+/// Issues not planned in any sprint
+pub async fn fetch_backlog<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+    page: u32,
+    per_page: u32,
+) -> ApiResult<PaginationResult<Issue>> {
+    let url = format!("/projects/{}/backlog", project_id);
+    let query_params = vec![
+        ("page", page.to_string()),
+        ("per_page", per_page.to_string()),
+        ("sort", "-createdAt".to_string()),
+    ];
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("backlog.list", request).await
+}
+
+pub async fn move_to_sprint(
+    ctx: &AuthContext,
+    project_id: &str,
+    issue_id: &str,
+    sprint_id: &str,
+) -> ApiResult<Issue> {
+    let url = format!("/projects/{}/issues/{}/sprint", project_id, issue_id);
+    let payload = UpdateIssueSprintBody {
+        sprint_id: Some(sprint_id.to_string()),
+    };
+    let request = ctx.client.post(&url).json(&payload);
+
+    ctx.client.send("backlog.move_to_sprint", request).await
+}
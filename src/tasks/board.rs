@@ -0,0 +1,33 @@
+use crate::{
+    error::ApiResult,
+    models::{
+        board::Board,
+        issue::{Issue, UpdateIssueStatusBody},
+    },
+};
+
+use super::{auth::AuthContext, client::Access};
+
+/// Fails with 404 when the project has no active sprint
+pub async fn fetch_board<A: Access>(ctx: &AuthContext<A>, project_id: &str) -> ApiResult<Board> {
+    let url = format!("/projects/{}/board", project_id);
+    let request = ctx.client.get(&url);
+
+    ctx.client.send("board.fetch", request).await
+}
+
+/// Same request as a status update, reported separately as a board move
+pub async fn move_board_issue(
+    ctx: &AuthContext,
+    project_id: &str,
+    issue_id: &str,
+    status_id: &str,
+) -> ApiResult<Issue> {
+    let url = format!("/projects/{}/issues/{}/status", project_id, issue_id);
+    let payload = UpdateIssueStatusBody {
+        status: status_id.to_string(),
+    };
+    let request = ctx.client.post(&url).json(&payload);
+
+    ctx.client.send("board.move", request).await
+}
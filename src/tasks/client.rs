@@ -0,0 +1,603 @@
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::time::{sleep, timeout};
+use tracing::warn;
+
+use crate::{
+    config::{ConnectionMode, GlobalConfig, TimeoutConfig},
+    error::{ApiError, ApiResult, RequestInfo, Result},
+    models::auth::AuthPayload,
+    shutdown::Shutdown,
+    stats::StatsCollector,
+};
+
+use super::{
+    JSON_CONTENT_TYPE, USER_AGENT,
+    retry::{RetryPolicy, parse_retry_after},
+};
+
+/// Creates the clients of all virtual users, they share the global request limit
+#[derive(Clone)]
+pub struct ClientFactory {
+    config: GlobalConfig,
+    stats: StatsCollector,
+    shutdown: Shutdown,
+    global_permits: Arc<Semaphore>,
+}
+
+/// Access level of a client, only read-write clients can build requests that
+/// mutate data so read-only simulations cannot write even by mistake
+pub trait Access: Clone + Send + Sync + 'static {}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReadWrite;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReadOnly;
+
+impl Access for ReadWrite {}
+impl Access for ReadOnly {}
+
+/// HTTP client of a single virtual user, clones share the same connection pool
+/// and request limit
+#[derive(Clone)]
+pub struct ApiClient<A = ReadWrite> {
+    access: PhantomData<A>,
+    api_url: String,
+    token: Option<String>,
+    client: Client,
+    retry: RetryPolicy,
+    timeouts: TimeoutConfig,
+    stats: StatsCollector,
+    shutdown: Shutdown,
+    global_permits: Arc<Semaphore>,
+    user_permits: Arc<Semaphore>,
+}
+
+// The token stays out of logs
+impl<A> fmt::Debug for ApiClient<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiClient")
+            .field("api_url", &self.api_url)
+            .field("authenticated", &self.token.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Request being built, keeps the method and URL around for error reporting
+pub struct ApiRequest {
+    method: Method,
+    url: String,
+    builder: RequestBuilder,
+}
+
+impl ApiRequest {
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        ApiRequest {
+            builder: self.builder.query(query),
+            ..self
+        }
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        ApiRequest {
+            builder: self.builder.json(json),
+            ..self
+        }
+    }
+}
+
+impl ClientFactory {
+    pub fn new(config: &GlobalConfig, stats: &StatsCollector, shutdown: &Shutdown) -> Self {
+        ClientFactory {
+            config: config.clone(),
+            stats: stats.clone(),
+            shutdown: shutdown.clone(),
+            global_permits: Arc::new(Semaphore::new(config.concurrency.max_in_flight)),
+        }
+    }
+
+    pub fn stats(&self) -> &StatsCollector {
+        &self.stats
+    }
+
+    pub fn shutdown(&self) -> &Shutdown {
+        &self.shutdown
+    }
+
+    /// New client with its own connection pool, one per virtual user
+    pub fn create<A: Access>(&self) -> Result<ApiClient<A>> {
+        ApiClient::new(
+            &self.config,
+            &self.stats,
+            &self.shutdown,
+            self.global_permits.clone(),
+        )
+    }
+}
+
+impl<A: Access> ApiClient<A> {
+    fn new(
+        config: &GlobalConfig,
+        stats: &StatsCollector,
+        shutdown: &Shutdown,
+        global_permits: Arc<Semaphore>,
+    ) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(JSON_CONTENT_TYPE));
+
+        let mut builder = Client::builder()
+            .user_agent(USER_AGENT)
+            .default_headers(headers)
+            .connect_timeout(Duration::from_millis(config.timeouts.connect_ms));
+
+        // Without idle connections, every request opens a new TCP/TLS connection
+        if config.connection_mode == ConnectionMode::Fresh {
+            builder = builder.pool_max_idle_per_host(0);
+        }
+
+        Ok(ApiClient {
+            access: PhantomData,
+            api_url: config.api_url.clone(),
+            token: None,
+            client: builder.build()?,
+            retry: RetryPolicy::from(&config.retry),
+            timeouts: config.timeouts.clone(),
+            stats: stats.clone(),
+            shutdown: shutdown.clone(),
+            global_permits,
+            user_permits: Arc::new(Semaphore::new(config.concurrency.max_in_flight_per_user)),
+        })
+    }
+
+    /// Copy of this client sending the bearer token on every request
+    pub fn with_token(&self, token: &str) -> Self {
+        ApiClient {
+            token: Some(token.to_string()),
+            ..self.clone()
+        }
+    }
+
+    pub fn stats(&self) -> &StatsCollector {
+        &self.stats
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn get(&self, path: &str) -> ApiRequest {
+        self.request(Method::GET, path)
+    }
+
+    /// Logging in is the only POST a read-only client is allowed to send
+    pub fn login(&self, payload: &AuthPayload) -> ApiRequest {
+        self.request(Method::POST, "/auth/token/email")
+            .json(payload)
+    }
+
+    fn request(&self, method: Method, path: &str) -> ApiRequest {
+        let url = format!("{}{}", self.api_url, path);
+        let mut builder = self.client.request(method.clone(), url.as_str());
+        if let Some(token) = self.token.as_ref() {
+            builder = builder.bearer_auth(token);
+        }
+
+        ApiRequest {
+            method,
+            url,
+            builder,
+        }
+    }
+
+    /// Sends the request, decodes the JSON response and records the outcome
+    /// under the given logical endpoint name, ie: `issues.fetch`
+    ///
+    /// Transient failures are retried according to the retry policy, only the
+    /// final attempt counts as a request while earlier ones count as retries.
+    /// Once shutdown is requested, requests that did not start yet are skipped
+    /// without being counted
+    pub async fn send<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        request: ApiRequest,
+    ) -> ApiResult<T> {
+        self.send_with(endpoint, request, decode_json).await
+    }
+
+    /// Same as `send` for calls whose response body is not used, ie: a `204`
+    /// of a delete, any successful response counts as a success
+    pub async fn send_empty(&self, endpoint: &str, request: ApiRequest) -> ApiResult<()> {
+        self.send_with(endpoint, request, |_| Ok(())).await
+    }
+
+    async fn send_with<T>(
+        &self,
+        endpoint: &str,
+        request: ApiRequest,
+        decode: fn(&str) -> std::result::Result<T, String>,
+    ) -> ApiResult<T> {
+        let ApiRequest {
+            method,
+            url,
+            mut builder,
+        } = request;
+        let mut attempt: u32 = 1;
+        // Failure waiting to be counted, as a retry once the next attempt runs
+        let mut previous: Option<ApiError> = None;
+
+        loop {
+            // Streaming bodies cannot be cloned, those requests are never retried
+            let next_builder = if attempt < self.retry.max_attempts() {
+                builder.try_clone()
+            } else {
+                None
+            };
+
+            let info = RequestInfo {
+                endpoint: endpoint.to_string(),
+                method: method.clone(),
+                url: url.clone(),
+                elapsed: None,
+            };
+            let result = self.attempt(info, builder, decode).await;
+
+            // A shutdown before the next attempt makes the previous one final
+            if let Some(error) = previous.take() {
+                match &result {
+                    Err(failure) if failure.error.is_cancelled() => {
+                        self.record_failure(endpoint, &error)
+                    }
+                    _ => self.stats.record_retry(endpoint),
+                }
+            }
+            let failure = match result {
+                Ok(data) => return Ok(data),
+                Err(failure) => failure,
+            };
+
+            match next_builder {
+                Some(next_builder) if failure.retryable && !self.shutdown.is_requested() => {
+                    let delay = self.retry.delay(attempt, failure.retry_after);
+                    warn!(
+                        "Retrying {} in {} ms after {}",
+                        endpoint,
+                        delay.as_millis(),
+                        failure.error.class()
+                    );
+
+                    tokio::select! {
+                        _ = sleep(delay) => {}
+                        _ = self.shutdown.requested() => {}
+                    }
+                    previous = Some(failure.error);
+                    builder = next_builder;
+                    attempt += 1;
+                }
+                _ if failure.error.is_cancelled() => return Err(failure.error),
+                _ => {
+                    self.record_failure(endpoint, &failure.error);
+                    return Err(failure.error);
+                }
+            }
+        }
+    }
+
+    async fn attempt<T>(
+        &self,
+        mut info: RequestInfo,
+        builder: RequestBuilder,
+        decode: fn(&str) -> std::result::Result<T, String>,
+    ) -> std::result::Result<T, AttemptFailure> {
+        let (read_timeout, total_timeout) = self.timeouts_for(&info.endpoint);
+        let builder = builder.timeout(total_timeout);
+
+        // Waiting for a free slot is not part of the request latency, the user
+        // slot is taken first so a busy user does not hog global slots
+        let _user_permit = self.user_permits.acquire().await;
+        let _global_permit = self.global_permits.acquire().await;
+        if self.shutdown.is_requested() {
+            return Err(AttemptFailure {
+                retryable: false,
+                retry_after: None,
+                error: ApiError::cancelled(info),
+            });
+        }
+
+        let _in_flight = self.stats.start_request();
+        let timer = Instant::now();
+        let response = match timeout(read_timeout, builder.send()).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(err)) => {
+                let class = classify_error(&err);
+                return Err(self.network_failure(info, class, error_chain(&err)));
+            }
+            Err(_) => {
+                let message = format!("No response within {} ms", read_timeout.as_millis());
+                return Err(self.network_failure(info, "read_timeout", message));
+            }
+        };
+
+        let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
+        let body = read_body(response, read_timeout).await;
+        info.elapsed = Some(timer.elapsed());
+
+        let body = match body {
+            Ok(body) => body,
+            Err((class, message)) => return Err(self.network_failure(info, class, message)),
+        };
+
+        if !status.is_success() {
+            return Err(AttemptFailure {
+                retryable: self.retry.is_retryable_status(&info.method, status),
+                retry_after,
+                error: ApiError::status(info, status, &body),
+            });
+        }
+
+        match decode(&body) {
+            Ok(data) => {
+                self.stats
+                    .record(&info.endpoint, timer.elapsed().as_millis());
+                Ok(data)
+            }
+            Err(message) => Err(AttemptFailure {
+                retryable: false,
+                retry_after: None,
+                error: ApiError::decode(info, message, &body),
+            }),
+        }
+    }
+
+    fn record_failure(&self, endpoint: &str, error: &ApiError) {
+        let duration = error.request().elapsed.map(|value| value.as_millis());
+        self.stats
+            .record_failure(endpoint, duration, &error.class(), &error.sample());
+    }
+
+    /// Read and total timeouts of the endpoint, falling back to the global ones
+    fn timeouts_for(&self, endpoint: &str) -> (Duration, Duration) {
+        let config = &self.timeouts;
+        let overrides = config.endpoints.get(endpoint);
+        let read_ms = overrides
+            .and_then(|value| value.read_ms)
+            .unwrap_or(config.read_ms);
+        let total_ms = overrides
+            .and_then(|value| value.total_ms)
+            .unwrap_or(config.total_ms);
+
+        (
+            Duration::from_millis(read_ms),
+            Duration::from_millis(total_ms),
+        )
+    }
+
+    fn network_failure(
+        &self,
+        info: RequestInfo,
+        class: &'static str,
+        message: String,
+    ) -> AttemptFailure {
+        AttemptFailure {
+            retryable: self.retry.is_retryable_error(&info.method, class),
+            retry_after: None,
+            error: ApiError::network(info, class, message),
+        }
+    }
+}
+
+impl ApiClient<ReadWrite> {
+    pub fn post(&self, path: &str) -> ApiRequest {
+        self.request(Method::POST, path)
+    }
+
+    pub fn patch(&self, path: &str) -> ApiRequest {
+        self.request(Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> ApiRequest {
+        self.request(Method::DELETE, path)
+    }
+}
+
+/// Decodes a JSON body, the error points at the field that did not match
+fn decode_json<T: DeserializeOwned>(body: &str) -> std::result::Result<T, String> {
+    let mut deserializer = serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|err| format!("{} at {}", err.inner(), err.path()))
+}
+
+/// Reads the whole body, failing when the server stalls between chunks
+async fn read_body(
+    mut response: Response,
+    read_timeout: Duration,
+) -> std::result::Result<String, (&'static str, String)> {
+    let mut body: Vec<u8> = Vec::new();
+
+    loop {
+        match timeout(read_timeout, response.chunk()).await {
+            Ok(Ok(Some(chunk))) => body.extend_from_slice(&chunk),
+            Ok(Ok(None)) => break,
+            Ok(Err(err)) => return Err((classify_error(&err), error_chain(&err))),
+            Err(_) => {
+                let message = format!("Body stalled for {} ms", read_timeout.as_millis());
+                return Err(("read_timeout", message));
+            }
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Failed attempt, only recorded once it is known that it won't be retried
+struct AttemptFailure {
+    error: ApiError,
+    retryable: bool,
+    retry_after: Option<Duration>,
+}
+
+/// Error class of a request that did not produce a usable response
+fn classify_error(err: &reqwest::Error) -> &'static str {
+    // Reqwest reports the total timeout as a timeout, a connect timeout as both
+    if err.is_timeout() {
+        return if err.is_connect() {
+            "connect_timeout"
+        } else {
+            "timeout"
+        };
+    }
+
+    // TLS handshake failures are reported as connect errors, look deeper
+    let mut source = std::error::Error::source(err);
+    while let Some(inner) = source {
+        if inner.downcast_ref::<native_tls::Error>().is_some() {
+            return "tls";
+        }
+        source = inner.source();
+    }
+
+    if err.is_connect() {
+        "connect"
+    } else if err.is_decode() || err.is_body() {
+        "body"
+    } else {
+        "request"
+    }
+}
+
+/// Joins the error with all of its sources, reqwest keeps the interesting part deep inside
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut messages = vec![err.to_string()];
+    let mut source = err.source();
+    while let Some(inner) = source {
+        messages.push(inner.to_string());
+        source = inner.source();
+    }
+    messages.join(": ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_classify_error() {
+        // Words in the URL must not make a refused connection look like TLS
+        let client = Client::new();
+        let err = client
+            .get("http://127.0.0.1:1/ssl/tls-certificate")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(classify_error(&err), "connect");
+
+        // Plain HTTP answer to a TLS handshake
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n").await;
+        });
+        let err = client
+            .get(format!("https://{}/api", addr))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(classify_error(&err), "tls");
+    }
+
+    #[tokio::test]
+    async fn test_send_empty() {
+        // Deletes answered with a 204 and no body
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let response = b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n";
+                let _ = stream.write_all(response).await;
+            }
+        });
+
+        let config: GlobalConfig = toml::from_str(&format!(
+            "api_url = \"http://{}/api\"\njwt_secret = \"secret\"",
+            addr
+        ))
+        .unwrap();
+        let stats = StatsCollector::new();
+        let clients = ClientFactory::new(&config, &stats, &Shutdown::default());
+        let client = clients.create::<ReadWrite>().unwrap();
+
+        let request = client.delete("/comments/c1");
+        assert!(client.send_empty("comments.delete", request).await.is_ok());
+
+        let request = client.delete("/comments/c1");
+        let err = client
+            .send::<serde::de::IgnoredAny>("comments.delete", request)
+            .await
+            .unwrap_err();
+        assert_eq!(err.class(), "decode");
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.overall.total_requests, 2);
+        assert_eq!(snapshot.overall.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_during_backoff() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let response = b"HTTP/1.1 503 Service Unavailable\r\nConnection: close\r\n\r\n";
+                let _ = stream.write_all(response).await;
+            }
+        });
+
+        let config: GlobalConfig = toml::from_str(&format!(
+            "api_url = \"http://{}/api\"\njwt_secret = \"secret\"\n\
+             [retry]\nbackoff_base_ms = 60000\nbackoff_cap_ms = 60000\njitter = 0.0",
+            addr
+        ))
+        .unwrap();
+        let stats = StatsCollector::new();
+        let shutdown = Shutdown::default();
+        let clients = ClientFactory::new(&config, &stats, &shutdown);
+        let client = clients.create::<ReadWrite>().unwrap();
+
+        let stopping = shutdown.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(200)).await;
+            stopping.request();
+        });
+
+        // The 503 is the final attempt, not a retry
+        let request = client.get("/projects");
+        let err = timeout(
+            Duration::from_secs(5),
+            client.send::<serde::de::IgnoredAny>("projects.list", request),
+        )
+        .await
+        .expect("Backoff must stop on shutdown")
+        .unwrap_err();
+        assert!(err.is_cancelled());
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.overall.total_requests, 1);
+        assert_eq!(snapshot.overall.retries, 0);
+        assert!(snapshot.overall.errors.contains_key("http_503"));
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{
+    error::ApiResult,
+    models::{
+        comment::{Comment, CreateCommentBody, UpdateCommentBody},
+        pagination::PaginationResult,
+    },
+};
+
+use super::{auth::AuthContext, client::Access};
+
+pub async fn fetch_comments<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+    issue_id: &str,
+    page: u32,
+    per_page: u32,
+) -> ApiResult<PaginationResult<Comment>> {
+    let url = format!("/projects/{}/issues/{}/comments", project_id, issue_id);
+    let query_params = vec![
+        ("page", page.to_string()),
+        ("per_page", per_page.to_string()),
+        ("sort", "createdAt".to_string()),
+    ];
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("comments.list", request).await
+}
+
+pub async fn create_comment(
+    ctx: &AuthContext,
+    project_id: &str,
+    issue_id: &str,
+    payload: &CreateCommentBody,
+) -> ApiResult<Comment> {
+    let url = format!("/projects/{}/issues/{}/comments", project_id, issue_id);
+    let request = ctx.client.post(&url).json(payload);
+
+    ctx.client.send("comments.create", request).await
+}
+
+pub async fn update_comment(
+    ctx: &AuthContext,
+    project_id: &str,
+    issue_id: &str,
+    comment_id: &str,
+    payload: &UpdateCommentBody,
+) -> ApiResult<Comment> {
+    let url = format!(
+        "/projects/{}/issues/{}/comments/{}",
+        project_id, issue_id, comment_id
+    );
+    let request = ctx.client.patch(&url).json(payload);
+
+    ctx.client.send("comments.update", request).await
+}
+
+pub async fn delete_comment(
+    ctx: &AuthContext,
+    project_id: &str,
+    issue_id: &str,
+    comment_id: &str,
+) -> ApiResult<()> {
+    let url = format!(
+        "/projects/{}/issues/{}/comments/{}",
+        project_id, issue_id, comment_id
+    );
+    let request = ctx.client.delete(&url);
+
+    ctx.client.send_empty("comments.delete", request).await
+}
@@ -1,22 +1,22 @@
-use anyhow::anyhow;
-use reqwest::Client;
 use std::time::Instant;
-use tracing::{error, info};
+use tracing::info;
 
 use crate::{
-    error::Result,
+    error::ApiResult,
     models::{
-        auth::AuthContext,
-        issue::{CreateIssueBody, Issue},
+        issue::{CreateIssueBody, Issue, IssuePartial, UpdateIssueStatusBody},
         pagination::PaginationResult,
         response_data::ResponseData,
     },
 };
 
-use super::{JSON_CONTENT_TYPE, USER_AGENT};
+use super::{auth::AuthContext, client::Access};
 
-pub async fn fetch_initiatives(ctx: &AuthContext, project_id: &str) -> Result<Vec<Issue>> {
-    let url = format!("{}/projects/{}/issues", ctx.api_url.as_str(), project_id);
+pub async fn fetch_initiatives<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> ApiResult<Vec<Issue>> {
+    let url = format!("/projects/{}/issues", project_id);
     let query_params = vec![
         ("type", "initiative".to_string()),
         ("state", "active".to_string()),
@@ -28,27 +28,15 @@ pub async fn fetch_initiatives(ctx: &AuthContext, project_id: &str) -> Result<Ve
             "createdBy,assignee,developmentUpdates,isFollower,subtasksCount".to_string(),
         ),
     ];
-    let response = Client::new()
-        .get(url)
-        .query(&query_params)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let issues: Vec<Issue> = response.json().await?;
-        Ok(issues)
-    } else {
-        Err(anyhow!(
-            "Unable to fetch epics. Error: {}",
-            response.status()
-        ))
-    }
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("issues.initiatives", request).await
 }
-pub async fn fetch_epics(ctx: &AuthContext, project_id: &str) -> Result<Vec<Issue>> {
-    let url = format!("{}/projects/{}/issues", ctx.api_url.as_str(), project_id);
+pub async fn fetch_epics<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> ApiResult<Vec<Issue>> {
+    let url = format!("/projects/{}/issues", project_id);
     let query_params = vec![
         ("type", "epic".to_string()),
         ("state", "active".to_string()),
@@ -60,31 +48,16 @@ pub async fn fetch_epics(ctx: &AuthContext, project_id: &str) -> Result<Vec<Issu
             "createdBy,assignee,developmentUpdates,isFollower,subtasksCount".to_string(),
         ),
     ];
-    let response = Client::new()
-        .get(url)
-        .query(&query_params)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let issues: Vec<Issue> = response.json().await?;
-        Ok(issues)
-    } else {
-        Err(anyhow!(
-            "Unable to fetch epics. Error: {}",
-            response.status()
-        ))
-    }
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("issues.epics", request).await
 }
 
 pub async fn create_issue(
     ctx: &AuthContext,
     project_id: &str,
     payload: &CreateIssueBody,
-) -> Result<ResponseData<Issue>> {
+) -> ApiResult<ResponseData<Issue>> {
     let mut res: ResponseData<Issue> = ResponseData {
         duration: 0,
         data: None,
@@ -93,13 +66,16 @@ pub async fn create_issue(
     let d = Instant::now();
     let create_res = do_create_issue(ctx, project_id, payload).await;
     res.duration = d.elapsed().as_millis();
-    if let Ok(issue_res) = create_res {
-        info!(
-            "{}: {} --> {} ms",
-            issue_res.key, issue_res.title, res.duration
-        );
-        res.data = Some(issue_res);
-    }
+    match create_res {
+        Ok(issue_res) => {
+            info!(
+                "{}: {} --> {} ms",
+                issue_res.key, issue_res.title, res.duration
+            );
+            res.data = Some(issue_res);
+        }
+        Err(e) => return Err(e),
+    };
 
     Ok(res)
 }
@@ -108,38 +84,22 @@ async fn do_create_issue(
     ctx: &AuthContext,
     project_id: &str,
     payload: &CreateIssueBody,
-) -> Result<Issue> {
-    let url = format!("{}/projects/{}/issues", ctx.api_url.as_str(), project_id);
-    let post_body = serde_json::to_string(payload)?;
-
-    let response = Client::new()
-        .post(url)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .body(post_body)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let issue: Issue = response.json().await?;
-        Ok(issue)
-    } else {
-        let message = format!("Unable to create issue. Error: {}", response.status());
-        error!("{}", message);
-        Err(anyhow!(message))
-    }
+) -> ApiResult<Issue> {
+    let url = format!("/projects/{}/issues", project_id);
+    let request = ctx.client.post(&url).json(payload);
+
+    ctx.client.send("issues.create", request).await
 }
 
-pub async fn fetch_issues(
-    ctx: &AuthContext,
+pub async fn fetch_issues<A: Access>(
+    ctx: &AuthContext<A>,
     project_id: Option<&str>,
     page: u32,
     per_page: u32,
-) -> Result<PaginationResult<Issue>> {
+) -> ApiResult<PaginationResult<Issue>> {
     let url = match project_id {
-        Some(pid) => format!("{}/projects/{}/issues", ctx.api_url.as_str(), pid),
-        None => format!("{}/issues", ctx.api_url.as_str()),
+        Some(pid) => format!("/projects/{}/issues", pid),
+        None => "/issues".to_string(),
     };
 
     let query_params = vec![
@@ -153,33 +113,16 @@ pub async fn fetch_issues(
         ),
     ];
 
-    let response = Client::new()
-        .get(url)
-        .query(&query_params)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let result: PaginationResult<Issue> = response.json().await?;
-        Ok(result)
-    } else {
-        let message = format!(
-            "Unable to fetch issue listing. Error: {}",
-            response.status()
-        );
-        error!("{}", message);
-        Err(anyhow!(message))
-    }
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("issues.list", request).await
 }
 
-pub async fn fetch_issue(
-    ctx: &AuthContext,
+pub async fn fetch_issue<A: Access>(
+    ctx: &AuthContext<A>,
     project_id: &str,
     issue_id: &str,
-) -> Result<ResponseData<Issue>> {
+) -> ApiResult<ResponseData<Issue>> {
     let mut res: ResponseData<Issue> = ResponseData {
         duration: 0,
         data: None,
@@ -194,46 +137,66 @@ pub async fn fetch_issue(
             info!("{}: {} --> {} ms", issue.key, issue.title, res.duration);
             res.data = Some(issue);
         }
-        Err(e) => {
-            error!("Error: {}", e);
-        }
+        Err(e) => return Err(e),
     };
 
     Ok(res)
 }
 
-async fn do_fetch_issue(ctx: &AuthContext, project_id: &str, issue_id: &str) -> Result<Issue> {
-    let url = format!(
-        "{}/projects/{}/issues/{}",
-        ctx.api_url.as_str(),
-        project_id,
-        issue_id
-    );
+async fn do_fetch_issue<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+    issue_id: &str,
+) -> ApiResult<Issue> {
+    let url = format!("/projects/{}/issues/{}", project_id, issue_id);
     let query_params = vec![(
         "include",
         "isCreator,isAssignee,isFollower,initiative,epic,parent,commitment,subtasksCount"
             .to_string(),
     )];
 
-    let response = Client::new()
-        .get(&url)
-        .query(&query_params)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let issue: Issue = response.json().await?;
-        Ok(issue)
-    } else {
-        let message = format!(
-            "Unable to fetch issue. Error: {}, URL={}",
-            response.status(),
-            url
-        );
-        error!("{}", message);
-        Err(anyhow!(message))
-    }
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("issues.fetch", request).await
+}
+
+pub async fn update_issue_status(
+    ctx: &AuthContext,
+    project_id: &str,
+    issue_id: &str,
+    status_id: &str,
+) -> ApiResult<Issue> {
+    let url = format!("/projects/{}/issues/{}/status", project_id, issue_id);
+    let payload = UpdateIssueStatusBody {
+        status: status_id.to_string(),
+    };
+    let request = ctx.client.post(&url).json(&payload);
+
+    ctx.client.send("issues.update_status", request).await
+}
+
+pub async fn update_issue(
+    ctx: &AuthContext,
+    project_id: &str,
+    issue_id: &str,
+    payload: &IssuePartial,
+) -> ApiResult<Issue> {
+    let url = format!("/projects/{}/issues/{}", project_id, issue_id);
+    let request = ctx.client.patch(&url).json(payload);
+
+    ctx.client.send("issues.update", request).await
+}
+
+pub async fn follow_issue(ctx: &AuthContext, project_id: &str, issue_id: &str) -> ApiResult<()> {
+    let url = format!("/projects/{}/issues/{}/followers", project_id, issue_id);
+    let request = ctx.client.post(&url);
+
+    ctx.client.send_empty("issues.follow", request).await
+}
+
+pub async fn unfollow_issue(ctx: &AuthContext, project_id: &str, issue_id: &str) -> ApiResult<()> {
+    let url = format!("/projects/{}/issues/{}/followers", project_id, issue_id);
+    let request = ctx.client.delete(&url);
+
+    ctx.client.send_empty("issues.unfollow", request).await
 }
@@ -1,5 +1,8 @@
 pub mod auth;
+pub mod backlog;
+pub mod board;
 pub mod channels;
+pub mod client;
 pub mod comments;
 pub mod iam;
 pub mod issues;
@@ -7,8 +10,10 @@ pub mod notifications;
 pub mod organisations;
 pub mod projects;
 pub mod repositories;
+pub mod retry;
 pub mod runner;
 pub mod sprints;
+pub mod think_time;
 pub mod timeline;
 pub mod user;
 
@@ -0,0 +1,20 @@
+use crate::{
+    error::ApiResult,
+    models::{notification::Notification, pagination::PaginationResult},
+};
+
+use super::{auth::AuthContext, client::Access};
+
+pub async fn fetch_notifications<A: Access>(
+    ctx: &AuthContext<A>,
+    page: u32,
+    per_page: u32,
+) -> ApiResult<PaginationResult<Notification>> {
+    let query_params = vec![
+        ("page", page.to_string()),
+        ("per_page", per_page.to_string()),
+    ];
+    let request = ctx.client.get("/notifications").query(&query_params);
+
+    ctx.client.send("notifications.list", request).await
+}
@@ -1,16 +1,16 @@
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
-use anyhow::anyhow;
 use fake::Fake;
 use fake::faker::company::en::CatchPhrase;
-use reqwest::Client;
+use rand::rngs::StdRng;
 use tokio::time::sleep;
 use tracing::{error, info};
 
+use super::{auth::AuthContext, client::Access};
+
 use crate::{
-    error::Result,
+    error::ApiResult,
     models::{
-        auth::AuthContext,
         issue_status::IssueStatus,
         label::Label,
         pagination::PaginationResult,
@@ -19,14 +19,12 @@ use crate::{
     },
 };
 
-use super::{JSON_CONTENT_TYPE, USER_AGENT};
-
-pub async fn fetch_projects(
-    ctx: &AuthContext,
+pub async fn fetch_projects<A: Access>(
+    ctx: &AuthContext<A>,
     page: u32,
     per_page: u32,
-) -> Result<PaginationResult<Project>> {
-    let url = format!("{}/projects", ctx.api_url.as_str());
+) -> ApiResult<PaginationResult<Project>> {
+    let url = "/projects".to_string();
     let query_params = vec![
         ("status", "active".to_string()),
         ("page", page.to_string()),
@@ -38,57 +36,29 @@ pub async fn fetch_projects(
         ),
     ];
 
-    let response = Client::new()
-        .get(url)
-        .query(&query_params)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let result: PaginationResult<Project> = response.json().await?;
-        Ok(result)
-    } else {
-        let message = format!(
-            "Unable to fetch project listing. Error: {}",
-            response.status()
-        );
-        eprintln!("{}", message);
-        Err(anyhow!(message))
-    }
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("projects.list", request).await
 }
 
-pub async fn fetch_project(ctx: &AuthContext, project_id: &str) -> Result<Project> {
-    let url = format!("{}/projects/{}", ctx.api_url.as_str(), project_id);
+pub async fn fetch_project<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> ApiResult<Project> {
+    let url = format!("/projects/{}", project_id);
     let query_params = vec![("include", "organisation".to_string())];
-    let response = Client::new()
-        .get(url)
-        .query(&query_params)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let project: Project = response.json().await?;
-        Ok(project)
-    } else {
-        Err(anyhow!(
-            "Unable to fetch project {}. Error: {}",
-            project_id,
-            response.status()
-        ))
-    }
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("projects.fetch", request).await
 }
 
-pub async fn fetch_project_with_retries(
-    ctx: &AuthContext,
+/// Polls a freshly created project until it becomes accessible, unlike
+/// transient failures a missing project is not retried by the client itself
+pub async fn fetch_project_with_retries<A: Access>(
+    ctx: &AuthContext<A>,
     project_id: &str,
     retries: u32,
-) -> Result<Project> {
+) -> ApiResult<Project> {
     let mut attempts: u32 = 0;
 
     loop {
@@ -99,75 +69,45 @@ pub async fn fetch_project_with_retries(
 
                 attempts += 1;
                 if attempts > retries {
-                    break;
+                    return Err(e);
                 }
 
-                sleep(Duration::from_secs(3)).await;
+                sleep(ctx.client.retry_policy().backoff(attempts)).await;
             }
         }
     }
-
-    Err(anyhow!("Unable to fetch project {}", project_id,))
 }
 
-pub async fn fetch_labels(ctx: &AuthContext, project_id: &str) -> Result<Vec<Label>> {
-    let url = format!("{}/projects/{}/labels", ctx.api_url.as_str(), project_id);
-    let response = Client::new()
-        .get(url)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let labels: Vec<Label> = response.json().await?;
-        Ok(labels)
-    } else {
-        Err(anyhow!(
-            "Unable to fetch project labels {}. Error: {}",
-            project_id,
-            response.status()
-        ))
-    }
+pub async fn fetch_labels<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> ApiResult<Vec<Label>> {
+    let url = format!("/projects/{}/labels", project_id);
+    let request = ctx.client.get(&url);
+
+    ctx.client.send("projects.labels", request).await
 }
 
-pub async fn fetch_statuses(ctx: &AuthContext, project_id: &str) -> Result<Vec<IssueStatus>> {
-    let url = format!(
-        "{}/projects/{}/issueStatuses",
-        ctx.api_url.as_str(),
-        project_id
-    );
-    let response = Client::new()
-        .get(url)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let statuses: Vec<IssueStatus> = response.json().await?;
-        Ok(statuses)
-    } else {
-        Err(anyhow!(
-            "Unable to fetch project issue statuses {}. Error: {}",
-            project_id,
-            response.status()
-        ))
-    }
+pub async fn fetch_statuses<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> ApiResult<Vec<IssueStatus>> {
+    let url = format!("/projects/{}/issueStatuses", project_id);
+    let request = ctx.client.get(&url);
+
+    ctx.client.send("projects.statuses", request).await
 }
 
-pub async fn create_project(ctx: &AuthContext) -> Result<Project> {
+pub async fn create_project(ctx: &AuthContext, rng: &mut StdRng) -> ApiResult<Project> {
     // Create project
-    let name: String = CatchPhrase().fake();
-    let key = generate_project_key(name.as_str());
+    let name: String = CatchPhrase().fake_with_rng(rng);
+    let key = generate_project_key(rng, name.as_str());
     let description = format!(
         "{}, {}, {}, {}",
-        CatchPhrase().fake::<String>(),
-        CatchPhrase().fake::<String>(),
-        CatchPhrase().fake::<String>(),
-        CatchPhrase().fake::<String>()
+        CatchPhrase().fake_with_rng::<String, _>(rng),
+        CatchPhrase().fake_with_rng::<String, _>(rng),
+        CatchPhrase().fake_with_rng::<String, _>(rng),
+        CatchPhrase().fake_with_rng::<String, _>(rng)
     );
     let payload = CreateProjectBody {
         key,
@@ -195,29 +135,13 @@ pub async fn create_project(ctx: &AuthContext) -> Result<Project> {
             );
             Ok(project_res)
         }
-        Err(e) => Err(anyhow!("Error: {}", e)),
+        Err(e) => Err(e),
     }
 }
 
-async fn do_create_project(ctx: &AuthContext, payload: &CreateProjectBody) -> Result<Project> {
-    let url = format!("{}/projects", ctx.api_url.as_str());
-    let post_body = serde_json::to_string(payload)?;
-
-    let response = Client::new()
-        .post(url)
-        .header(reqwest::header::USER_AGENT, USER_AGENT)
-        .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
-        .body(post_body)
-        .bearer_auth(ctx.token.as_str())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let project: Project = response.json().await?;
-        Ok(project)
-    } else {
-        let message = format!("Unable to create issue. Error: {}", response.status());
-        error!("{}", message);
-        Err(anyhow!(message))
-    }
+async fn do_create_project(ctx: &AuthContext, payload: &CreateProjectBody) -> ApiResult<Project> {
+    let url = "/projects".to_string();
+    let request = ctx.client.post(&url).json(payload);
+
+    ctx.client.send("projects.create", request).await
 }
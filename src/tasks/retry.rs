@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+use crate::config::RetryConfig;
+
+/// Decides which failed attempts are retried and how long to wait in between
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff_base: Duration,
+    backoff_cap: Duration,
+    jitter: f64,
+    retry_statuses: Vec<u16>,
+    retry_network_errors: bool,
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        RetryPolicy {
+            max_attempts: config.max_attempts.max(1),
+            backoff_base: Duration::from_millis(config.backoff_base_ms),
+            backoff_cap: Duration::from_millis(config.backoff_cap_ms),
+            jitter: config.jitter.clamp(0.0, 1.0),
+            retry_statuses: config.retry_statuses.clone(),
+            retry_network_errors: config.retry_network_errors,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Writes are only retried after a 429, with other statuses the server
+    /// may already have applied them
+    pub fn is_retryable_status(&self, method: &Method, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16())
+            && (is_idempotent(method) || status == StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// Network failures worth another attempt, TLS errors are not transient.
+    /// Writes are only retried when they never reached the server
+    pub fn is_retryable_error(&self, method: &Method, class: &str) -> bool {
+        if !self.retry_network_errors {
+            return false;
+        }
+        match class {
+            "connect" | "connect_timeout" => true,
+            "timeout" | "read_timeout" | "body" => is_idempotent(method),
+            _ => false,
+        }
+    }
+
+    /// Exponential backoff capped at the configured maximum, the jitter part
+    /// is subtracted so that the cap is never exceeded
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .backoff_base
+            .saturating_mul(factor)
+            .min(self.backoff_cap);
+
+        if self.jitter == 0.0 {
+            return delay;
+        }
+
+        let spread = rand::rng().random_range(0.0..=self.jitter);
+        delay.mul_f64(1.0 - spread)
+    }
+
+    /// Delay before the next attempt, a server provided Retry-After wins over
+    /// the backoff but is still bounded by the cap
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(value) => value.min(self.backoff_cap),
+            None => self.backoff(attempt),
+        }
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE
+    )
+}
+
+/// Reads Retry-After as either delay seconds or an HTTP date
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&Utc) - Utc::now();
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_and_retry_after() {
+        let config = RetryConfig {
+            max_attempts: 5,
+            backoff_base_ms: 100,
+            backoff_cap_ms: 1000,
+            jitter: 0.0,
+            retry_statuses: vec![503],
+            retry_network_errors: false,
+        };
+        let policy = RetryPolicy::from(&config);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_millis(1000));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(30))),
+            Duration::from_millis(1000)
+        );
+        assert!(policy.is_retryable_status(&Method::GET, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.is_retryable_status(&Method::GET, StatusCode::NOT_FOUND));
+        assert!(!policy.is_retryable_error(&Method::GET, "connect"));
+
+        let jittered = RetryPolicy::from(&RetryConfig {
+            jitter: 1.0,
+            ..config
+        });
+        assert!(jittered.backoff(2) <= Duration::from_millis(200));
+
+        let defaults = RetryPolicy::from(&RetryConfig::default());
+        assert!(!defaults.is_retryable_status(&Method::POST, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(defaults.is_retryable_status(&Method::POST, StatusCode::TOO_MANY_REQUESTS));
+        assert!(defaults.is_retryable_status(&Method::DELETE, StatusCode::BAD_GATEWAY));
+        assert!(defaults.is_retryable_error(&Method::POST, "connect_timeout"));
+        assert!(!defaults.is_retryable_error(&Method::PATCH, "read_timeout"));
+        assert!(defaults.is_retryable_error(&Method::GET, "body"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+}
@@ -1,52 +1,80 @@
 use anyhow::anyhow;
-use bigdecimal::BigDecimal;
+use chrono::{Days, NaiveDate, Utc};
 use fake::Fake;
 use fake::faker::company::en::CatchPhrase;
-use rand::Rng;
-use std::time::Instant;
-use tokio::task::JoinSet;
-use tracing::{error, info};
+use fake::faker::lorem::en::Sentence;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+use tokio::task::{AbortHandle, JoinSet};
+use tokio::time::{interval, sleep_until};
+use tracing::{error, info, warn};
 
 use crate::{
-    config::{Config, GlobalConfig, SingleTargetConfig},
-    error::Result,
+    config::{
+        BacklogConfig, Config, Credential, GlobalConfig, MultiTargetConfig, ScenarioConfig,
+        ScenarioTask, SingleTargetConfig, StepCondition,
+    },
+    error::{ApiError, ApiResult, Result},
     models::{
-        auth::{AuthContext, AuthPayload},
-        issue::{CreateIssueBody, Issue},
+        auth::AuthPayload,
+        comment::{CreateCommentBody, UpdateCommentBody},
+        issue::{CreateIssueBody, Issue, IssuePartial},
         issue_status::IssueStatus,
+        label::Label,
+        project::Project,
+        project_member::ProjectMember,
+        sprint::{CreateSprintBody, Sprint, StartSprintBody},
     },
+    shutdown::Shutdown,
     tasks::{
-        auth::authenticate,
-        iam::fetch_project_members,
-        issues::{create_issue, fetch_epics, fetch_initiatives, fetch_issue, fetch_issues},
+        auth::{AuthContext, authenticate, fetch_project_authz},
+        backlog::{fetch_backlog, move_to_sprint},
+        board::{fetch_board, move_board_issue},
+        channels::fetch_project_channel,
+        client::{Access, ApiClient, ClientFactory, ReadOnly, ReadWrite},
+        comments::{create_comment, delete_comment, fetch_comments, update_comment},
+        iam::{fetch_iam, fetch_my_organisation, fetch_project_members},
+        issues::{
+            create_issue, fetch_epics, fetch_initiatives, fetch_issue, fetch_issues, follow_issue,
+            unfollow_issue, update_issue, update_issue_status,
+        },
+        notifications::fetch_notifications,
         projects::{
-            create_project, fetch_labels, fetch_project, fetch_project_with_retries, fetch_statuses,
+            create_project, fetch_labels, fetch_project, fetch_project_with_retries,
+            fetch_projects, fetch_statuses,
         },
+        repositories::fetch_project_repositories,
+        sprints::{
+            complete_sprint, create_sprint, fetch_sprint_issues, fetch_sprints, start_sprint,
+        },
+        think_time::ThinkTime,
+        timeline::fetch_activity_logs,
+        user::{fetch_current_user, fetch_user_preferences},
     },
     token::create_captcha_token,
 };
 
+// Sprints kept planned ahead of the active one
+const PLANNED_SPRINTS: usize = 2;
+const SPRINT_DAYS: u64 = 14;
+
 pub async fn run_create_seed_project(
     config: &GlobalConfig,
     single_target: &Option<SingleTargetConfig>,
+    clients: &ClientFactory,
+    seed: u64,
 ) -> Result<()> {
     // Authenticate
-    let api_url = config.api_url.as_str();
-    let jwt_secret = config.jwt_secret.as_str();
     let Some(target) = single_target.as_ref() else {
         return Err(anyhow!("Single target config must be present."));
     };
-    let captcha_token = create_captcha_token(jwt_secret)?;
-    let payload = AuthPayload {
-        username: target.username.clone(),
-        password: target.password.clone(),
-        captcha_token,
-    };
-    let context = authenticate(api_url, payload).await?;
-    info!("Logged in as {}", context.user.username);
+    let client = clients.create()?;
+    let context = login(&client, config, &target.username, &target.password).await?;
+    let mut rng = user_rng(seed, 0);
 
     // Create project
-    let project = create_project(&context).await?;
+    let project = create_project(&context, &mut rng).await?;
 
     // Check until project is accessible
     let _ = fetch_project_with_retries(&context, project.id.as_str(), 5).await?;
@@ -64,7 +92,7 @@ pub async fn run_create_seed_project(
         issue_type: Some("epic".to_string()),
     };
 
-    let _ = run_create_issues(config, &Some(epic_config)).await?;
+    create_issues(config, &epic_config, clients, &mut rng).await?;
 
     let issue_config = SingleTargetConfig {
         username: target.username.clone(),
@@ -73,7 +101,7 @@ pub async fn run_create_seed_project(
         issue_count: 50,
         issue_type: Some("user_story".to_string()),
     };
-    let _ = run_create_issues(config, &Some(issue_config)).await?;
+    create_issues(config, &issue_config, clients, &mut rng).await?;
 
     Ok(())
 }
@@ -81,492 +109,1751 @@ pub async fn run_create_seed_project(
 pub async fn run_create_issues(
     config: &GlobalConfig,
     single_target: &Option<SingleTargetConfig>,
+    clients: &ClientFactory,
+    seed: u64,
 ) -> Result<()> {
-    let timer = Instant::now();
-    // Authenticate
-    let api_url = config.api_url.as_str();
-    let jwt_secret = config.jwt_secret.as_str();
     let Some(target) = single_target else {
         return Err(anyhow!("Single target config must be present."));
     };
-    let captcha_token = create_captcha_token(jwt_secret)?;
-    let payload = AuthPayload {
-        username: target.username.clone(),
-        password: target.password.clone(),
-        captcha_token,
-    };
-    let context = authenticate(api_url, payload).await?;
-    info!("Logged in as {}", context.user.username);
+    create_issues(config, target, clients, &mut user_rng(seed, 0)).await
+}
+
+async fn create_issues(
+    config: &GlobalConfig,
+    target: &SingleTargetConfig,
+    clients: &ClientFactory,
+    rng: &mut StdRng,
+) -> Result<()> {
+    // Authenticate
+    let client = clients.create()?;
+    let context = login(&client, config, &target.username, &target.password).await?;
 
     let project_id = target.project_id.as_str();
-    let project = fetch_project(&context, project_id).await?;
-    info!("{}: {}", project.key, project.name);
+    let data = load_project_data(&context, project_id).await?;
+    info!("{}: {}", data.project.key, data.project.name);
+
+    let mut set = JoinSet::new();
+
+    for _ in 0..target.issue_count {
+        let payload = random_issue_payload(rng, &data, target.issue_type.as_deref())?;
+
+        // Why do I have to clone everything?
+        let context_copy = context.clone();
+        let payload_copy = payload.clone();
+        let project_id_copy = project_id.to_string();
+
+        set.spawn(async move {
+            create_issue(&context_copy, project_id_copy.as_str(), &payload_copy).await
+        });
+    }
+
+    // Samples are recorded by the tasks themselves, only log failures here
+    while let Some(join_res) = set.join_next().await {
+        match join_res {
+            Ok(res) => {
+                if let Err(create_err) = res
+                    && !create_err.is_cancelled()
+                {
+                    error!("Error: {}", create_err);
+                }
+            }
+            Err(join_err) => {
+                error!("Error: {:?}", join_err);
+            }
+        }
+    }
+
+    Ok(())
+}
 
-    let labels = fetch_labels(&context, project_id).await?;
+/// When repeated runs stop, whichever limit is reached first or shutdown
+#[derive(Clone, Debug, Default)]
+pub struct RunLimits {
+    pub duration: Option<Duration>,
+    pub deadline: Option<Instant>,
+    pub iterations: Option<u32>,
+    pub shutdown: Shutdown,
+}
 
-    let mut statuses = fetch_statuses(&context, project_id).await?;
-    // Remove last status, should not create issues as done
-    if statuses.len() > 0 {
-        statuses.pop();
+impl RunLimits {
+    pub fn new(duration: Option<Duration>, iterations: Option<u32>, shutdown: &Shutdown) -> Self {
+        RunLimits {
+            duration,
+            deadline: duration.map(|value| Instant::now() + value),
+            iterations,
+            shutdown: shutdown.clone(),
+        }
     }
 
-    let initiatives = fetch_initiatives(&context, project_id).await?;
-    let epics = fetch_epics(&context, project_id).await?;
-    let members = fetch_project_members(&context, project_id).await?;
-    let hours = vec![
-        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
-    ];
-    let points = vec![1, 2, 3, 5, 8, 13, 21];
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
 
-    let Some(pref) = project.preferences else {
-        return Err(anyhow!("Project preferences must be present."));
+    /// Deadline reached or shutdown requested, no more work should start
+    pub fn is_stopped(&self) -> bool {
+        self.is_expired() || self.shutdown.is_requested()
+    }
+
+    /// Whether another iteration may start after `done` of them
+    pub fn allows(&self, done: u32) -> bool {
+        !self.is_stopped() && self.iterations.is_none_or(|limit| done < limit)
+    }
+
+    /// Crawls run once unless told to repeat
+    fn once_by_default(&self) -> Self {
+        match (self.deadline, self.iterations) {
+            (None, None) => RunLimits {
+                iterations: Some(1),
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+
+    async fn until_deadline(&self) {
+        match self.deadline {
+            Some(deadline) => sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+pub async fn run_crawl_all_issues(
+    config: &Config,
+    clients: &ClientFactory,
+    limits: &RunLimits,
+) -> Result<()> {
+    run_crawl(config, clients, limits, false).await
+}
+
+pub async fn run_crawl_issues(
+    config: &Config,
+    clients: &ClientFactory,
+    limits: &RunLimits,
+) -> Result<()> {
+    run_crawl(config, clients, limits, true).await
+}
+
+/// Crawls all issues of the user, or of the target project only, as many
+/// times as the limits allow
+async fn run_crawl(
+    config: &Config,
+    clients: &ClientFactory,
+    limits: &RunLimits,
+    project_only: bool,
+) -> Result<()> {
+    // Authenticate
+    let global = &config.global;
+    let Some(target) = config.single_target.as_ref() else {
+        return Err(anyhow!("Single target config must be present."));
+    };
+    let client = clients.create::<ReadOnly>()?;
+    let mut context = login(&client, global, &target.username, &target.password).await?;
+
+    let project_id = if project_only {
+        let project = fetch_project(&context, &target.project_id).await?;
+        info!("{}: {}", project.key, project.name);
+        Some(target.project_id.as_str())
+    } else {
+        None
     };
 
-    let issue_type = match target.issue_type.as_ref() {
-        Some(value) => value.as_str(),
-        None => pref.issue_type.as_str(),
+    let limits = limits.once_by_default();
+    let mut done = 0;
+    while limits.allows(done) {
+        let mut has_more = true;
+        let mut page = 1;
+        let mut reauthenticated = false;
+
+        while has_more && !limits.is_stopped() {
+            // Fetch listing
+            let listing = match fetch_issues(&context, project_id, page, 50).await {
+                Ok(listing) => listing,
+                // Token expired mid-crawl, log in again and retry the same page once
+                Err(err) if err.is_unauthorized() && !reauthenticated => {
+                    context = login(&client, global, &target.username, &target.password).await?;
+                    reauthenticated = true;
+                    continue;
+                }
+                Err(err) if err.is_cancelled() => break,
+                Err(err) => return Err(err.into()),
+            };
+            reauthenticated = false;
+
+            has_more = false;
+            if !listing.data.is_empty() && listing.meta.total_records > 0 {
+                if crawl_issue_batch(&context, listing.data).await {
+                    context = login(&client, global, &target.username, &target.password).await?;
+                }
+
+                // See if there are still more items
+                if listing.meta.total_pages > page {
+                    page += 1;
+                    has_more = true;
+                }
+            }
+        }
+
+        if has_more {
+            info!("Stopping the crawl before page {}", page);
+            break;
+        }
+
+        done += 1;
+        if limits.iterations != Some(1) {
+            info!("Crawl {} done", done);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn run_users_read_write(
+    config: &Config,
+    clients: &ClientFactory,
+    limits: &RunLimits,
+    seed: u64,
+) -> Result<()> {
+    run_users::<ReadWrite>(config, clients, limits, seed).await
+}
+
+/// Same as the read-write simulation but users only browse, the read-only
+/// clients cannot build requests that change anything
+pub async fn run_users_readonly(
+    config: &Config,
+    clients: &ClientFactory,
+    limits: &RunLimits,
+    seed: u64,
+) -> Result<()> {
+    if let Some(target) = config.multi_target.as_ref() {
+        for user in target.users.iter() {
+            let Some(scenario) = config.scenario_of(user) else {
+                continue;
+            };
+            if let Some(step) = scenario.steps.iter().find(|step| step.task.is_write()) {
+                return Err(anyhow!(
+                    "Scenario step {:?} of {} changes data and cannot run read-only.",
+                    step.task,
+                    user.username
+                ));
+            }
+        }
+    }
+
+    run_users::<ReadOnly>(config, clients, limits, seed).await
+}
+
+/// What a simulated user does in each of their projects
+trait UserMode: Access {
+    fn work_on_project(
+        ctx: &AuthContext<Self>,
+        project: &Project,
+        target: &MultiTargetConfig,
+        think_time: &ThinkTime,
+        rng: &mut StdRng,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn run_step(
+        ctx: &AuthContext<Self>,
+        task: ScenarioTask,
+        state: &mut ScenarioState,
+        target: &MultiTargetConfig,
+        rng: &mut StdRng,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl UserMode for ReadWrite {
+    fn work_on_project(
+        ctx: &AuthContext<Self>,
+        project: &Project,
+        target: &MultiTargetConfig,
+        think_time: &ThinkTime,
+        rng: &mut StdRng,
+    ) -> impl Future<Output = Result<()>> + Send {
+        run_tasks(ctx, project, target, think_time, rng)
+    }
+
+    fn run_step(
+        ctx: &AuthContext<Self>,
+        task: ScenarioTask,
+        state: &mut ScenarioState,
+        target: &MultiTargetConfig,
+        rng: &mut StdRng,
+    ) -> impl Future<Output = Result<()>> + Send {
+        run_write_step(ctx, task, state, target, rng)
+    }
+}
+
+impl UserMode for ReadOnly {
+    fn work_on_project(
+        ctx: &AuthContext<Self>,
+        project: &Project,
+        _target: &MultiTargetConfig,
+        think_time: &ThinkTime,
+        rng: &mut StdRng,
+    ) -> impl Future<Output = Result<()>> + Send {
+        browse_project(ctx, project, think_time, rng)
+    }
+
+    fn run_step(
+        ctx: &AuthContext<Self>,
+        task: ScenarioTask,
+        state: &mut ScenarioState,
+        _target: &MultiTargetConfig,
+        rng: &mut StdRng,
+    ) -> impl Future<Output = Result<()>> + Send {
+        run_read_step(ctx, task, state, rng)
+    }
+}
+
+async fn run_users<A: UserMode>(
+    config: &Config,
+    clients: &ClientFactory,
+    limits: &RunLimits,
+    seed: u64,
+) -> Result<()> {
+    let Some(target) = config.multi_target.as_ref() else {
+        return Err(anyhow!("Multi target config must be present."));
     };
+    if !target.stages.is_empty() {
+        return run_stages::<A>(config, clients, target, limits, seed).await;
+    }
 
-    let create_timer = Instant::now();
+    // Log in everyone first so that the simulation starts all at once
+    let mut logins = JoinSet::new();
+    for (index, user) in target.users.iter().enumerate() {
+        let client = clients.create::<A>()?;
+        let global = config.global.clone();
+        let user = user.clone();
+        logins.spawn(async move {
+            let context = login(&client, &global, &user.username, &user.password).await;
+            context.map(|context| (index, context))
+        });
+    }
 
-    let mut set = JoinSet::new();
+    // Keep the position of the user, it picks the random stream of the user
+    let mut contexts: Vec<(usize, AuthContext<A>)> = Vec::new();
+    while let Some(join_res) = logins.join_next().await {
+        match join_res {
+            Ok(Ok(context)) => contexts.push(context),
+            Ok(Err(err)) => error!("Error: {}", err),
+            Err(err) => error!("Error: {:?}", err),
+        }
+    }
+    if limits.is_stopped() {
+        return Ok(());
+    }
+    if contexts.is_empty() {
+        return Err(anyhow!("None of the users were able to log in."));
+    }
+    info!("{} users logged in, press Ctrl-C to stop", contexts.len());
+
+    let mut users = JoinSet::new();
+    for (index, context) in contexts {
+        let target = target.clone();
+        let global = config.global.clone();
+        let credential = target.users[index].clone();
+        let scenario = config.scenario_of(&credential);
+        let limits = limits.clone();
+        let rng = user_rng(seed, index as u64);
+        users.spawn(async move {
+            simulate_user(
+                context,
+                &credential,
+                &global,
+                &target,
+                scenario.as_ref(),
+                &limits,
+                rng,
+            )
+            .await
+        });
+    }
 
-    for _ in 0..target.issue_count {
-        let member = get_random_item(&members, 30);
-        let label = get_random_item(&labels, 30);
+    // Users stop on their own after their iterations or on shutdown, the
+    // deadline stops new requests and lets the ones in flight be recorded
+    let mut expired = false;
+    loop {
+        tokio::select! {
+            join_res = users.join_next() => match join_res {
+                Some(Ok(Ok(()))) => {}
+                Some(Ok(Err(err))) if is_cancelled(&err) => {}
+                Some(Ok(Err(err))) => error!("Error: {}", err),
+                Some(Err(err)) => error!("Error: {:?}", err),
+                None => break,
+            },
+            _ = limits.until_deadline(), if !expired => {
+                info!("Duration reached, waiting for users to stop...");
+                limits.shutdown.expire();
+                expired = true;
+            }
+        }
+    }
 
-        let mut initiative: Option<&Issue> = None;
-        let mut epic: Option<&Issue> = None;
-        let mut status: Option<&IssueStatus> = None;
+    Ok(())
+}
 
-        // Initiatives and epics do not have these properties
-        match issue_type {
-            "initiative" => {
-                // Do nothing...
+/// Starts and stops virtual users to follow the load stages, virtual users
+/// log in when they start and take turns using the configured credentials
+async fn run_stages<A: UserMode>(
+    config: &Config,
+    clients: &ClientFactory,
+    target: &MultiTargetConfig,
+    limits: &RunLimits,
+    seed: u64,
+) -> Result<()> {
+    let mut users = JoinSet::new();
+    // Oldest first, ramping down stops the newest users
+    let mut active: Vec<AbortHandle> = Vec::new();
+    let mut started: usize = 0;
+    // Users done with their iterations are not replaced
+    let mut retired: usize = 0;
+    let mut previous: usize = 0;
+
+    'stages: for (index, stage) in target.stages.iter().enumerate() {
+        let name = match stage.name.as_ref() {
+            Some(name) => name.clone(),
+            None => format!("stage-{}", index + 1),
+        };
+        info!(
+            "Stage {}: {} to {} users over {}",
+            name,
+            previous,
+            stage.target,
+            humantime::format_duration(stage.duration)
+        );
+        clients.stats().start_stage(&name);
+
+        let timer = Instant::now();
+        let mut ticker = interval(Duration::from_secs(1));
+        loop {
+            let progress = if stage.duration.is_zero() {
+                1.0
+            } else {
+                (timer.elapsed().as_secs_f64() / stage.duration.as_secs_f64()).min(1.0)
+            };
+            let wanted = previous as f64 + (stage.target as f64 - previous as f64) * progress;
+            let wanted = (wanted.round() as usize).saturating_sub(retired);
+
+            while active.len() < wanted {
+                let credential = target.users[started % target.users.len()].clone();
+                let rng = user_rng(seed, started as u64);
+                started += 1;
+
+                let client = clients.create::<A>()?;
+                let global = config.global.clone();
+                let target = target.clone();
+                let scenario = config.scenario_of(&credential);
+                let limits = limits.clone();
+                active.push(users.spawn(async move {
+                    let context =
+                        login(&client, &global, &credential.username, &credential.password).await?;
+                    simulate_user(
+                        context,
+                        &credential,
+                        &global,
+                        &target,
+                        scenario.as_ref(),
+                        &limits,
+                        rng,
+                    )
+                    .await
+                }));
             }
-            "epic" => {
-                initiative = get_random_item(&initiatives, 20);
+            while active.len() > wanted {
+                if let Some(user) = active.pop() {
+                    user.abort();
+                }
             }
-            _ => {
-                epic = get_random_item(&epics, 20);
-                status = get_random_item(&statuses, 100);
+
+            if progress >= 1.0 {
+                break;
             }
-        };
 
-        let default_labels: Vec<String> = vec![];
+            // Users that stopped on their own are replaced on the next tick
+            tokio::select! {
+                _ = ticker.tick() => {}
+                Some(join_res) = users.join_next_with_id() => match join_res {
+                    Ok((id, result)) => {
+                        active.retain(|user| user.id() != id);
+                        match result {
+                            Ok(()) => retired += 1,
+                            Err(err) if is_cancelled(&err) => {}
+                            Err(err) => error!("Error: {}", err),
+                        }
+                    }
+                    Err(err) => {
+                        active.retain(|user| user.id() != err.id());
+                        if !err.is_cancelled() {
+                            error!("Error: {:?}", err);
+                        }
+                    }
+                },
+                _ = limits.until_deadline() => {
+                    info!("Duration reached");
+                    limits.shutdown.expire();
+                    break 'stages;
+                }
+                _ = limits.shutdown.requested() => break 'stages,
+            }
+        }
 
-        let title: String = CatchPhrase().fake();
-        let description = format!(
-            "{}, {}, {}, {}",
-            CatchPhrase().fake::<String>(),
-            CatchPhrase().fake::<String>(),
-            CatchPhrase().fake::<String>(),
-            CatchPhrase().fake::<String>()
+        previous = stage.target;
+    }
+
+    if limits.shutdown.is_requested() {
+        // Users stop on their own once their requests in flight are done
+        info!("Waiting for {} users to stop...", active.len());
+        while let Some(join_res) = users.join_next().await {
+            if let Ok(Err(err)) = join_res
+                && !is_cancelled(&err)
+            {
+                error!("Error: {}", err);
+            }
+        }
+    } else {
+        info!("All stages done, stopping {} users", active.len());
+        users.abort_all();
+    }
+    clients.stats().finish_stage();
+
+    Ok(())
+}
+
+/// Runs the scenario over and over, or without one visits the account pages
+/// then walks through all projects of the user doing work in each, each pass
+/// counts as an iteration. The credential logs the user back in once the
+/// session expires
+async fn simulate_user<A: UserMode>(
+    mut context: AuthContext<A>,
+    credential: &Credential,
+    config: &GlobalConfig,
+    target: &MultiTargetConfig,
+    scenario: Option<&ScenarioConfig>,
+    limits: &RunLimits,
+    mut rng: StdRng,
+) -> Result<()> {
+    let username = credential.username.as_str();
+    let think_time = ThinkTime::from(&config.think_time);
+
+    if let Some(scenario) = scenario {
+        info!(
+            "{} running scenario {}",
+            username,
+            scenario.name.as_deref().unwrap_or("unnamed")
         );
+        let mut done = 0;
+        while limits.allows(done) {
+            done += 1;
+            if let Err(err) = run_scenario(&context, scenario, target, &think_time, &mut rng).await
+            {
+                if is_cancelled(&err) {
+                    break;
+                }
+                error!("{}: {}", username, err);
+                if is_expired(&err) {
+                    context =
+                        login(&context.client, config, username, &credential.password).await?;
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let mut done = 0;
+    while limits.allows(done) {
+        done += 1;
+        if let Err(err) = browse_account(&context).await {
+            if is_cancelled(&err) {
+                break;
+            }
+            error!("{}: {}", username, err);
+            if is_expired(&err) {
+                context = login(&context.client, config, username, &credential.password).await?;
+            }
+        }
+        think_time.pause(&mut rng).await;
+
+        let projects = match fetch_all_projects(&context).await {
+            Ok(projects) => projects,
+            Err(err) if err.is_unauthorized() => {
+                context = login(&context.client, config, username, &credential.password).await?;
+                continue;
+            }
+            Err(err) if err.is_cancelled() => break,
+            Err(err) => return Err(err.into()),
+        };
+        if projects.is_empty() {
+            return Err(anyhow!("{} has no projects to work on.", username));
+        }
+
+        for project in projects.iter() {
+            if let Err(err) =
+                A::work_on_project(&context, project, target, &think_time, &mut rng).await
+            {
+                if is_cancelled(&err) {
+                    break;
+                }
+                error!("{}: {}", username, err);
+                if is_expired(&err) {
+                    context =
+                        login(&context.client, config, username, &credential.password).await?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
 
-        let mut payload = CreateIssueBody {
-            r#type: issue_type.to_string(),
-            initiative_id: None,
-            epic_id: None,
-            parent_id: None,
-            assignee_id: None,
-            title,
-            description: Some(description),
-            estimate_type: Some(pref.estimate_type.clone()),
-            estimate: Some(10),
-            status: None,
-            labels: default_labels,
+/// What the steps of a scenario pass loaded so far, later steps act on it
+#[derive(Default)]
+struct ScenarioState {
+    project: Option<ProjectData>,
+    issues: Vec<Issue>,
+}
+
+impl ScenarioState {
+    fn allows(&self, condition: StepCondition) -> bool {
+        match condition {
+            StepCondition::Always => true,
+            StepCondition::HasProject => self.project.is_some(),
+            StepCondition::HasIssues => !self.issues.is_empty(),
+            StepCondition::HasEpics => self.project.as_ref().is_some_and(|d| !d.epics.is_empty()),
+            StepCondition::HasMembers => {
+                self.project.as_ref().is_some_and(|d| !d.members.is_empty())
+            }
+            StepCondition::HasLabels => self.project.as_ref().is_some_and(|d| !d.labels.is_empty()),
+        }
+    }
+
+    fn project(&self) -> Result<&ProjectData> {
+        let Some(data) = self.project.as_ref() else {
+            return Err(anyhow!(
+                "No project picked yet, add a fetch_projects step first."
+            ));
         };
+        Ok(data)
+    }
 
-        if pref.estimate_type == String::from("points") {
-            let estimate = get_random_item(&points, 100);
-            payload.estimate = Some(*estimate.unwrap());
-        } else {
-            let estimate = get_random_item(&hours, 100);
-            payload.estimate = Some(*estimate.unwrap());
+    fn project_mut(&mut self) -> Result<&mut ProjectData> {
+        let Some(data) = self.project.as_mut() else {
+            return Err(anyhow!(
+                "No project picked yet, add a fetch_projects step first."
+            ));
+        };
+        Ok(data)
+    }
+}
+
+/// Single pass through the scenario steps, starting from a blank state
+async fn run_scenario<A: UserMode>(
+    ctx: &AuthContext<A>,
+    scenario: &ScenarioConfig,
+    target: &MultiTargetConfig,
+    think_time: &ThinkTime,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let mut state = ScenarioState::default();
+
+    for step in scenario.steps.iter() {
+        if !get_item_chance(rng, step.weight) || !state.allows(step.when) {
+            continue;
+        }
+        for _ in 0..step.loops {
+            A::run_step(ctx, step.task, &mut state, target, rng).await?;
+            think_time.pause(rng).await;
         }
+    }
 
-        if let Some(initiative_value) = initiative {
-            payload.initiative_id = Some(String::from(initiative_value.id.as_str()));
+    Ok(())
+}
+
+/// Runs a step that only reads, write steps need a read-write user
+async fn run_read_step<A: Access>(
+    ctx: &AuthContext<A>,
+    task: ScenarioTask,
+    state: &mut ScenarioState,
+    rng: &mut StdRng,
+) -> Result<()> {
+    match task {
+        ScenarioTask::FetchProjects => {
+            let projects = fetch_all_projects(ctx).await?;
+            state.project = get_random_item(rng, &projects, 100)
+                .cloned()
+                .map(ProjectData::new);
+            state.issues.clear();
+        }
+        ScenarioTask::FetchProject => {
+            let project_id = state.project()?.project.id.clone();
+            let _ = fetch_project_authz(ctx, &project_id).await?;
+            state.project_mut()?.project = fetch_project(ctx, &project_id).await?;
+        }
+        ScenarioTask::FetchStatuses => {
+            let project_id = state.project()?.project.id.clone();
+            state.project_mut()?.statuses = fetch_statuses(ctx, &project_id).await?;
+        }
+        ScenarioTask::FetchLabels => {
+            let project_id = state.project()?.project.id.clone();
+            state.project_mut()?.labels = fetch_labels(ctx, &project_id).await?;
+        }
+        ScenarioTask::FetchEpics => {
+            let project_id = state.project()?.project.id.clone();
+            let initiatives = fetch_initiatives(ctx, &project_id).await?;
+            let epics = fetch_epics(ctx, &project_id).await?;
+            let data = state.project_mut()?;
+            data.initiatives = initiatives;
+            data.epics = epics;
+        }
+        ScenarioTask::FetchMembers => {
+            let project_id = state.project()?.project.id.clone();
+            state.project_mut()?.members = fetch_project_members(ctx, &project_id).await?;
+        }
+        ScenarioTask::FetchIssues => {
+            let project_id = state.project()?.project.id.clone();
+            state.issues = fetch_issues(ctx, Some(&project_id), 1, 50).await?.data;
+        }
+        ScenarioTask::FetchIssue => {
+            if let Some(issue) = get_random_item(rng, &state.issues, 100) {
+                let _ = fetch_issue(ctx, &issue.project_id, &issue.id).await?;
+            }
+        }
+        ScenarioTask::FetchBoard => {
+            let project_id = state.project()?.project.id.clone();
+            state.issues = match fetch_board(ctx, &project_id).await {
+                Ok(board) => board.issues,
+                Err(err) if err.is_not_found() => Vec::new(),
+                Err(err) => return Err(err.into()),
+            };
+        }
+        ScenarioTask::FetchBacklog => {
+            let project_id = state.project()?.project.id.clone();
+            state.issues = fetch_backlog(ctx, &project_id, 1, 50).await?.data;
+        }
+        ScenarioTask::FetchSprints => {
+            let project_id = state.project()?.project.id.clone();
+            let _ = fetch_sprints(ctx, &project_id).await?;
         }
+        ScenarioTask::FetchComments => {
+            if let Some(issue) = get_random_item(rng, &state.issues, 100) {
+                let _ = fetch_comments(ctx, &issue.project_id, &issue.id, 1, 20).await?;
+            }
+        }
+        ScenarioTask::FetchNotifications => {
+            let _ = fetch_notifications(ctx, 1, 20).await?;
+        }
+        ScenarioTask::FetchActivityLogs => {
+            let _ = fetch_activity_logs(ctx, 1, 20).await?;
+        }
+        ScenarioTask::FetchAccount => {
+            let _ = fetch_iam(ctx).await?;
+            let _ = fetch_my_organisation(ctx).await?;
+            let _ = fetch_current_user(ctx).await?;
+            let _ = fetch_user_preferences(ctx).await?;
+        }
+        ScenarioTask::CreateIssue
+        | ScenarioTask::CreateEpic
+        | ScenarioTask::CycleSprints
+        | ScenarioTask::MoveBoardIssues
+        | ScenarioTask::GroomBacklog
+        | ScenarioTask::UpdateIssue
+        | ScenarioTask::MoveIssue
+        | ScenarioTask::CommentIssue
+        | ScenarioTask::FollowIssue => {
+            return Err(anyhow!(
+                "{:?} changes data and needs a read-write user.",
+                task
+            ));
+        }
+    }
 
-        if let Some(epic_value) = epic {
-            payload.epic_id = Some(String::from(epic_value.id.as_str()));
+    Ok(())
+}
+
+/// Runs any step, the ones that only read are handed over to `run_read_step`
+async fn run_write_step(
+    ctx: &AuthContext,
+    task: ScenarioTask,
+    state: &mut ScenarioState,
+    target: &MultiTargetConfig,
+    rng: &mut StdRng,
+) -> Result<()> {
+    match task {
+        ScenarioTask::CreateIssue => {
+            let data = state.project()?;
+            let payload = random_issue_payload(rng, data, target.issue_type.as_deref())?;
+            let created = create_issue(ctx, &data.project.id, &payload).await?;
+            state.issues.extend(created.data);
+        }
+        ScenarioTask::CreateEpic => {
+            let data = state.project()?;
+            let payload = random_issue_payload(rng, data, Some("epic"))?;
+            let created = create_issue(ctx, &data.project.id, &payload).await?;
+            state.project_mut()?.epics.extend(created.data);
+        }
+        ScenarioTask::CycleSprints => {
+            cycle_sprints(ctx, state.project()?, rng).await?;
+        }
+        ScenarioTask::MoveBoardIssues => {
+            move_board_issues(ctx, state.project()?, rng).await?;
+        }
+        ScenarioTask::GroomBacklog => {
+            groom_backlog(ctx, state.project()?, target, rng).await?;
         }
-        if let Some(member_value) = member {
-            if let Some(user_value) = &member_value.user {
-                payload.assignee_id = Some(String::from(user_value.id.as_str()));
+        ScenarioTask::UpdateIssue => {
+            let Some(issue) = get_random_item(rng, &state.issues, 100) else {
+                return Ok(());
+            };
+            let issue_id = issue.id.clone();
+            let updated = update_random_issue(ctx, state.project()?, issue, rng).await?;
+            if let Some(issue) = state.issues.iter_mut().find(|item| item.id == issue_id) {
+                *issue = updated;
             }
         }
-        if let Some(status_value) = status {
-            payload.status = Some(String::from(status_value.id.as_str()));
+        ScenarioTask::MoveIssue => {
+            let data = state.project()?;
+            let Some(issue) = get_random_item(rng, &state.issues, 100) else {
+                return Ok(());
+            };
+            if let Some(status) = next_status(&data.statuses, issue) {
+                let issue_id = issue.id.clone();
+                let status_id = status.id.clone();
+                update_issue_status(ctx, &data.project.id, &issue_id, &status_id).await?;
+                if let Some(issue) = state.issues.iter_mut().find(|item| item.id == issue_id) {
+                    issue.status = Some(status_id);
+                }
+            }
         }
-        if let Some(label_value) = label {
-            payload.labels = vec![String::from(label_value.id.as_str())];
+        ScenarioTask::CommentIssue => {
+            if let Some(issue) = get_random_item(rng, &state.issues, 100) {
+                let members = match state.project.as_ref() {
+                    Some(data) => data.members.as_slice(),
+                    None => &[],
+                };
+                comment_on_issue(ctx, members, issue, rng).await?;
+            }
+        }
+        ScenarioTask::FollowIssue => {
+            let Some(issue) = get_random_item(rng, &state.issues, 100) else {
+                return Ok(());
+            };
+            let issue_id = issue.id.clone();
+            let following = issue.is_follower == Some(true);
+            if following {
+                unfollow_issue(ctx, &issue.project_id, &issue_id).await?;
+            } else {
+                follow_issue(ctx, &issue.project_id, &issue_id).await?;
+            }
+            if let Some(issue) = state.issues.iter_mut().find(|item| item.id == issue_id) {
+                issue.is_follower = Some(!following);
+            }
         }
+        _ => run_read_step(ctx, task, state, rng).await?,
+    }
 
-        // Why do I have to clone everything?
-        let context_copy = context.clone();
-        let payload_copy = payload.clone();
-        let project_id_copy = project_id.to_string();
+    Ok(())
+}
 
+/// Whether the task failed because the token expired
+fn is_expired(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ApiError>()
+        .is_some_and(|api_err| api_err.is_unauthorized())
+}
+
+/// Whether the task was skipped because the run is shutting down
+fn is_cancelled(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ApiError>()
+        .is_some_and(|api_err| api_err.is_cancelled())
+}
+
+/// Logs in with a fresh captcha token, also used when the token expires
+async fn login<A: Access>(
+    client: &ApiClient<A>,
+    config: &GlobalConfig,
+    username: &str,
+    password: &str,
+) -> Result<AuthContext<A>> {
+    let captcha_token = create_captcha_token(&config.jwt_secret)?;
+    let payload = AuthPayload {
+        username: username.to_string(),
+        password: password.to_string(),
+        captcha_token,
+    };
+    let context = authenticate(client, payload).await?;
+    info!("Logged in as {}", context.user.username);
+
+    Ok(context)
+}
+
+/// Fetches every issue of the listing concurrently, returns true when the
+/// token expired along the way
+async fn crawl_issue_batch<A: Access>(context: &AuthContext<A>, issues: Vec<Issue>) -> bool {
+    let mut set = JoinSet::new();
+    for issue in issues {
+        let context_copy = context.clone();
         set.spawn(async move {
-            create_issue(&context_copy, project_id_copy.as_str(), &payload_copy).await
+            fetch_issue(&context_copy, issue.project_id.as_str(), issue.id.as_str()).await
         });
     }
 
-    // Gather stats
-    let total_reqs: u32 = set.len() as u32;
-    let mut failed: u32 = 0;
-    let mut min_duration: u128 = 0;
-    let mut max_duration: u128 = 0;
-    let mut sum: u128 = 0;
-
+    let mut expired = false;
     while let Some(join_res) = set.join_next().await {
         match join_res {
-            Ok(res) => match res {
-                Ok(issue_res) => {
-                    if let None = issue_res.data {
-                        failed += 1;
-                    }
+            Ok(Ok(_)) => {}
+            // Deleted between listing and fetching, nothing left to crawl
+            Ok(Err(err)) if err.is_not_found() => {
+                info!("Skipped missing issue: {}", err.request().url);
+            }
+            Ok(Err(err)) if err.is_cancelled() => {}
+            Ok(Err(err)) => {
+                expired |= err.is_unauthorized();
+                error!("Error: {}", err);
+            }
+            Err(err) => {
+                error!("Error: {:?}", err);
+            }
+        }
+    }
 
-                    sum += issue_res.duration;
+    expired
+}
 
-                    if min_duration == 0 {
-                        min_duration = issue_res.duration;
-                    } else if issue_res.duration < min_duration {
-                        min_duration = issue_res.duration;
-                    }
+/// Everything a user needs to know about a project before working in it
+struct ProjectData {
+    project: Project,
+    labels: Vec<Label>,
+    statuses: Vec<IssueStatus>,
+    initiatives: Vec<Issue>,
+    epics: Vec<Issue>,
+    members: Vec<ProjectMember>,
+}
 
-                    if issue_res.duration > max_duration {
-                        max_duration = issue_res.duration;
-                    }
-                }
-                Err(create_err) => {
-                    error!("Error: {:?}", create_err);
-                }
-            },
-            Err(join_err) => {
-                error!("Error: {:?}", join_err);
-            }
+impl ProjectData {
+    /// Only the project itself, the rest is loaded on demand
+    fn new(project: Project) -> Self {
+        ProjectData {
+            project,
+            labels: Vec::new(),
+            statuses: Vec::new(),
+            initiatives: Vec::new(),
+            epics: Vec::new(),
+            members: Vec::new(),
         }
     }
+}
 
-    let succeed = total_reqs - failed;
-    let big_success_ratio =
-        (BigDecimal::from(succeed) / BigDecimal::from(total_reqs)) * BigDecimal::from(100);
-    let success_ratio = big_success_ratio.round(2);
-    let big_sum = BigDecimal::from(sum);
-    let big_total_reqs = BigDecimal::from(total_reqs);
-    let big_avg = big_sum / big_total_reqs.clone();
-    let avg = big_avg.round(2);
-
-    let total_time = timer.elapsed().as_millis();
-    let total_create_time = create_timer.elapsed().as_millis();
-    let big_create_total_time = BigDecimal::from(total_create_time);
-    let big_rps: BigDecimal = big_total_reqs / (big_create_total_time / 1000.0);
-    let rps = big_rps.round(2);
-
-    // Print stats
-    println!("");
-    println!("Total requests: {}", total_reqs);
-    println!("Succeed: {}", succeed);
-    println!("Failed: {}", failed);
-    println!("Success rate: {}%", success_ratio);
-    println!("Min: {} ms", min_duration);
-    println!("Avg: {} ms", avg);
-    println!("Max: {} ms", max_duration);
-    println!("Requests per second: {}", rps);
-    println!("Run duration: {} ms", total_time);
+async fn load_project_data<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> Result<ProjectData> {
+    Ok(ProjectData {
+        project: fetch_project(ctx, project_id).await?,
+        labels: fetch_labels(ctx, project_id).await?,
+        statuses: fetch_statuses(ctx, project_id).await?,
+        initiatives: fetch_initiatives(ctx, project_id).await?,
+        epics: fetch_epics(ctx, project_id).await?,
+        members: fetch_project_members(ctx, project_id).await?,
+    })
+}
 
-    Ok(())
+async fn fetch_all_projects<A: Access>(ctx: &AuthContext<A>) -> ApiResult<Vec<Project>> {
+    let mut projects: Vec<Project> = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let listing = fetch_projects(ctx, page, 50).await?;
+        projects.extend(listing.data);
+
+        if listing.meta.total_pages <= page {
+            return Ok(projects);
+        }
+        page += 1;
+    }
 }
 
-pub async fn run_crawl_all_issues(config: Config) -> Result<()> {
-    let timer = Instant::now();
-    // Authenticate
-    let api_url = config.global.api_url.as_str();
-    let jwt_secret = config.global.jwt_secret.as_str();
-    let Some(target) = config.single_target else {
-        return Err(anyhow!("Single target config must be present."));
+fn random_issue_payload(
+    rng: &mut StdRng,
+    data: &ProjectData,
+    issue_type: Option<&str>,
+) -> Result<CreateIssueBody> {
+    let Some(pref) = data.project.preferences.as_ref() else {
+        return Err(anyhow!("Project preferences must be present."));
     };
-    let captcha_token = create_captcha_token(jwt_secret)?;
-    let payload = AuthPayload {
-        username: target.username,
-        password: target.password,
-        captcha_token,
+
+    let issue_type = issue_type.unwrap_or(pref.issue_type.as_str());
+
+    // Last status is done, should not create issues as done
+    let statuses = match data.statuses.split_last() {
+        Some((_, open)) => open,
+        None => &[],
     };
-    let context = authenticate(api_url, payload).await?;
-    info!("Logged in as {}", context.user.username);
 
-    let crawl_timer = Instant::now();
+    let member = get_random_item(rng, &data.members, 30);
+    let label = get_random_item(rng, &data.labels, 30);
 
-    // Gather stats
-    let mut total_reqs: u32 = 0;
-    let mut failed: u32 = 0;
-    let mut min_duration: u128 = 0;
-    let mut max_duration: u128 = 0;
-    let mut sum: u128 = 0;
+    let mut initiative: Option<&Issue> = None;
+    let mut epic: Option<&Issue> = None;
+    let mut status: Option<&IssueStatus> = None;
 
-    let mut has_more = true;
-    let mut page = 1;
+    // Initiatives and epics do not have these properties
+    match issue_type {
+        "initiative" => {
+            // Do nothing...
+        }
+        "epic" => {
+            initiative = get_random_item(rng, &data.initiatives, 20);
+        }
+        _ => {
+            epic = get_random_item(rng, &data.epics, 20);
+            status = get_random_item(rng, statuses, 100);
+        }
+    };
 
-    while has_more {
-        // Fetch listing
-        let listing = fetch_issues(&context, None, page, 50).await?;
+    let default_labels: Vec<String> = vec![];
+
+    let title: String = CatchPhrase().fake_with_rng(rng);
+    let description = format!(
+        "{}, {}, {}, {}",
+        CatchPhrase().fake_with_rng::<String, _>(rng),
+        CatchPhrase().fake_with_rng::<String, _>(rng),
+        CatchPhrase().fake_with_rng::<String, _>(rng),
+        CatchPhrase().fake_with_rng::<String, _>(rng)
+    );
+
+    let mut payload = CreateIssueBody {
+        r#type: issue_type.to_string(),
+        initiative_id: None,
+        epic_id: None,
+        parent_id: None,
+        assignee_id: None,
+        title,
+        description: Some(description),
+        estimate_type: Some(pref.estimate_type.clone()),
+        estimate: Some(random_estimate(rng, &pref.estimate_type)),
+        status: None,
+        labels: default_labels,
+    };
 
-        has_more = false;
-        if listing.data.len() > 0 && listing.meta.total_records > 0 {
-            // Queue current batch
-            let mut set = JoinSet::new();
-            for issue in listing.data {
-                let context_copy = context.clone();
-                let issue_id = issue.id.clone();
-                let project_id_copy = issue.project_id.clone();
+    if let Some(initiative_value) = initiative {
+        payload.initiative_id = Some(String::from(initiative_value.id.as_str()));
+    }
 
-                set.spawn(async move {
-                    fetch_issue(&context_copy, project_id_copy.as_str(), issue_id.as_str()).await
-                });
-            }
+    if let Some(epic_value) = epic {
+        payload.epic_id = Some(String::from(epic_value.id.as_str()));
+    }
+    if let Some(member_value) = member
+        && let Some(user_value) = &member_value.user
+    {
+        payload.assignee_id = Some(String::from(user_value.id.as_str()));
+    }
+    if let Some(status_value) = status {
+        payload.status = Some(String::from(status_value.id.as_str()));
+    }
+    if let Some(label_value) = label {
+        payload.labels = vec![String::from(label_value.id.as_str())];
+    }
 
-            let req_count: u32 = set.len() as u32;
-            total_reqs += req_count;
-
-            // Process batch
-            while let Some(join_res) = set.join_next().await {
-                match join_res {
-                    Ok(res) => match res {
-                        Ok(issue_res) => {
-                            if let None = issue_res.data {
-                                failed += 1;
-                            }
-
-                            sum += issue_res.duration;
-
-                            if min_duration == 0 {
-                                min_duration = issue_res.duration;
-                            } else if issue_res.duration < min_duration {
-                                min_duration = issue_res.duration;
-                            }
-
-                            if issue_res.duration > max_duration {
-                                max_duration = issue_res.duration;
-                            }
-                        }
-                        Err(issue_err) => {
-                            error!("Error: {:?}", issue_err);
-                        }
-                    },
-                    Err(err) => {
-                        error!("Error: {:?}", err);
-                    }
-                }
-            }
+    Ok(payload)
+}
+
+/// Story points or hours depending on the estimate type of the project
+fn random_estimate(rng: &mut StdRng, estimate_type: &str) -> u32 {
+    if estimate_type == "points" {
+        let points = [1, 2, 3, 5, 8, 13, 21];
+        points[rng.random_range(0..points.len())]
+    } else {
+        rng.random_range(1..=20)
+    }
+}
+
+/// Changes a random subset of the issue fields, at least the title
+fn random_issue_update(rng: &mut StdRng, data: &ProjectData, issue: &Issue) -> IssuePartial {
+    let mut update = IssuePartial::default();
+
+    if get_item_chance(rng, 40) {
+        update.title = Some(CatchPhrase().fake_with_rng(rng));
+    }
+    if get_item_chance(rng, 40) {
+        update.description = Some(CatchPhrase().fake_with_rng(rng));
+    }
+    if get_item_chance(rng, 40)
+        && let Some(pref) = data.project.preferences.as_ref()
+    {
+        update.estimate = Some(random_estimate(rng, &pref.estimate_type));
+    }
+    if let Some(label) = get_random_item(rng, &data.labels, 40) {
+        update.labels = Some(vec![label.id.clone()]);
+    }
+    if let Some(member) = get_random_item(rng, &data.members, 40)
+        && let Some(user) = member.user.as_ref()
+    {
+        update.assignee_id = Some(user.id.clone());
+    }
+    // Initiatives and epics do not belong to an epic
+    if !matches!(issue.r#type.as_str(), "initiative" | "epic")
+        && let Some(epic) = get_random_item(rng, &data.epics, 40)
+    {
+        update.epic_id = Some(epic.id.clone());
+    }
+
+    let changed = update.title.is_some()
+        || update.description.is_some()
+        || update.estimate.is_some()
+        || update.labels.is_some()
+        || update.assignee_id.is_some()
+        || update.epic_id.is_some();
+    if !changed {
+        update.title = Some(CatchPhrase().fake_with_rng(rng));
+    }
 
-            // See if there are still more items
-            if listing.meta.total_pages > page {
-                page += 1;
-                has_more = true;
+    update
+}
+
+/// Fails when the updated issue does not carry every changed field
+fn check_issue_update(update: &IssuePartial, issue: &Issue) -> Result<()> {
+    let mut mismatches: Vec<&str> = Vec::new();
+    if update
+        .title
+        .as_ref()
+        .is_some_and(|title| title != &issue.title)
+    {
+        mismatches.push("title");
+    }
+    if update.description.is_some() && update.description != issue.description {
+        mismatches.push("description");
+    }
+    if update.estimate.is_some() && update.estimate != issue.estimate {
+        mismatches.push("estimate");
+    }
+    if update.labels.is_some() && update.labels != issue.labels {
+        mismatches.push("labels");
+    }
+    if update.assignee_id.is_some() && update.assignee_id != issue.assignee_id {
+        mismatches.push("assignee");
+    }
+    if update.epic_id.is_some() && update.epic_id != issue.epic_id {
+        mismatches.push("epic");
+    }
+
+    if !mismatches.is_empty() {
+        return Err(anyhow!(
+            "Update of {} is not reflected in the response: {}",
+            issue.key,
+            mismatches.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+async fn update_random_issue(
+    ctx: &AuthContext,
+    data: &ProjectData,
+    issue: &Issue,
+    rng: &mut StdRng,
+) -> Result<Issue> {
+    let update = random_issue_update(rng, data, issue);
+    let updated = update_issue(ctx, &issue.project_id, &issue.id, &update).await?;
+
+    // Reported like a failed request, the round goes on with the next action
+    if let Err(err) = check_issue_update(&update, &updated) {
+        warn!("{}: {}", ctx.user.username, err);
+        ctx.client.stats().record_failure(
+            "issues.update.check",
+            None,
+            "mismatch",
+            &err.to_string(),
+        );
+    }
+
+    Ok(updated)
+}
+
+/// Picks an issue from the active sprint board or the backlog and updates it
+async fn update_board_or_backlog_issue(
+    ctx: &AuthContext,
+    data: &ProjectData,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let project_id = data.project.id.as_str();
+    let issues = if get_item_chance(rng, 50) {
+        match fetch_board(ctx, project_id).await {
+            Ok(board) => board.issues,
+            Err(err) if err.is_not_found() => Vec::new(),
+            Err(err) => return Err(err.into()),
+        }
+    } else {
+        fetch_backlog(ctx, project_id, 1, 50).await?.data
+    };
+
+    if let Some(issue) = get_random_item(rng, &issues, 100) {
+        let updated = update_random_issue(ctx, data, issue, rng).await?;
+        info!("{} updated {}", ctx.user.username, updated.key);
+    }
+
+    Ok(())
+}
+
+/// Does a round of work in a single project, ie: create issues, move them
+/// towards completion, comment and follow, pausing after each action
+async fn run_tasks(
+    ctx: &AuthContext,
+    project: &Project,
+    target: &MultiTargetConfig,
+    think_time: &ThinkTime,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let project_id = project.id.as_str();
+
+    let _ = fetch_project_authz(ctx, project_id).await?;
+    let data = load_project_data(ctx, project_id).await?;
+    info!(
+        "{} working on {}: {}",
+        ctx.user.username, data.project.key, data.project.name
+    );
+    think_time.pause(rng).await;
+
+    // Create a random number of issues
+    let issue_count = rng.random_range(0..=target.issue_count);
+    for _ in 0..issue_count {
+        let payload = random_issue_payload(rng, &data, target.issue_type.as_deref())?;
+        let _ = create_issue(ctx, project_id, &payload).await?;
+        think_time.pause(rng).await;
+    }
+
+    let issues = fetch_issues(ctx, Some(project_id), 1, 50).await?.data;
+    think_time.pause(rng).await;
+    for issue in issues.iter() {
+        // Move one step towards completion
+        if get_item_chance(rng, 20)
+            && let Some(next_status) = next_status(&data.statuses, issue)
+        {
+            update_issue_status(ctx, project_id, &issue.id, &next_status.id).await?;
+            think_time.pause(rng).await;
+        }
+
+        if get_item_chance(rng, 10) {
+            match issue.is_follower {
+                Some(true) => unfollow_issue(ctx, project_id, &issue.id).await?,
+                _ => follow_issue(ctx, project_id, &issue.id).await?,
             }
+            think_time.pause(rng).await;
         }
     }
 
-    let succeed = total_reqs - failed;
-    let big_success_ratio =
-        (BigDecimal::from(succeed) / BigDecimal::from(total_reqs)) * BigDecimal::from(100);
-    let success_ratio = big_success_ratio.round(2);
-    let big_sum = BigDecimal::from(sum);
-    let big_total_reqs = BigDecimal::from(total_reqs);
-    let big_avg = big_sum / big_total_reqs.clone();
-    let avg = big_avg.round(2);
-
-    let total_time = timer.elapsed().as_millis();
-    let total_crawl_time = crawl_timer.elapsed().as_millis();
-    let big_crawl_total_time = BigDecimal::from(total_crawl_time);
-    let big_rps: BigDecimal = big_total_reqs / (big_crawl_total_time / 1000.0);
-    let rps = big_rps.round(2);
-
-    // Print stats
-    println!("");
-    println!("Total requests: {}", total_reqs);
-    println!("Succeed: {}", succeed);
-    println!("Failed: {}", failed);
-    println!("Success rate: {}%", success_ratio);
-    println!("Min: {} ms", min_duration);
-    println!("Avg: {} ms", avg);
-    println!("Max: {} ms", max_duration);
-    println!("Requests per second: {}", rps);
-    println!("Run duration: {} ms", total_time);
+    cycle_sprints(ctx, &data, rng).await?;
+    think_time.pause(rng).await;
+    groom_backlog(ctx, &data, target, rng).await?;
+    think_time.pause(rng).await;
+    move_board_issues(ctx, &data, rng).await?;
+    think_time.pause(rng).await;
+    update_board_or_backlog_issue(ctx, &data, rng).await?;
+    think_time.pause(rng).await;
+    comment_on_board_and_backlog(ctx, &data, rng).await?;
+    think_time.pause(rng).await;
 
     Ok(())
 }
 
-pub async fn run_crawl_issues(config: Config) -> Result<()> {
-    let timer = Instant::now();
-    // Authenticate
-    let api_url = config.global.api_url.as_str();
-    let jwt_secret = config.global.jwt_secret.as_str();
-    let Some(target) = config.single_target else {
-        return Err(anyhow!("Single target config must be present."));
-    };
-    let captcha_token = create_captcha_token(jwt_secret)?;
-    let payload = AuthPayload {
-        username: target.username,
-        password: target.password,
-        captcha_token,
+/// Comments on random issues of the active sprint board and of the backlog
+async fn comment_on_board_and_backlog(
+    ctx: &AuthContext,
+    data: &ProjectData,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let project_id = data.project.id.as_str();
+    let mut issues = match fetch_board(ctx, project_id).await {
+        Ok(board) => board.issues,
+        Err(err) if err.is_not_found() => Vec::new(),
+        Err(err) => return Err(err.into()),
     };
-    let context = authenticate(api_url, payload).await?;
-    info!("Logged in as {}", context.user.username);
+    issues.extend(fetch_backlog(ctx, project_id, 1, 50).await?.data);
 
-    let project_id = target.project_id.as_str();
-    let project = fetch_project(&context, project_id).await?;
-    info!("{}: {}", project.key, project.name);
+    for issue in issues.iter() {
+        if get_item_chance(rng, 10) {
+            comment_on_issue(ctx, &data.members, issue, rng).await?;
+        }
+    }
 
-    let crawl_timer = Instant::now();
+    Ok(())
+}
 
-    // Gather stats
-    let mut total_reqs: u32 = 0;
-    let mut failed: u32 = 0;
-    let mut min_duration: u128 = 0;
-    let mut max_duration: u128 = 0;
-    let mut sum: u128 = 0;
+/// Reads the thread then adds a comment, which is sometimes edited or deleted
+/// right after like a user fixing a typo or changing their mind
+async fn comment_on_issue(
+    ctx: &AuthContext,
+    members: &[ProjectMember],
+    issue: &Issue,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let project_id = issue.project_id.as_str();
+    let _ = fetch_comments(ctx, project_id, &issue.id, 1, 20).await?;
 
-    let mut has_more = true;
-    let mut page = 1;
+    let payload = random_comment(rng, members);
+    let comment = create_comment(ctx, project_id, &issue.id, &payload).await?;
 
-    while has_more {
-        // Fetch listing
-        let listing = fetch_issues(&context, Some(project_id), page, 50).await?;
+    if get_item_chance(rng, 20) {
+        let edited = random_comment(rng, members);
+        let payload = UpdateCommentBody {
+            body: edited.body,
+            body_data: edited.body_data,
+        };
+        update_comment(ctx, project_id, &issue.id, &comment.id, &payload).await?;
+    } else if get_item_chance(rng, 10) {
+        delete_comment(ctx, project_id, &issue.id, &comment.id).await?;
+    }
 
-        has_more = false;
-        if listing.data.len() > 0 && listing.meta.total_records > 0 {
-            // Queue current batch
-            let mut set = JoinSet::new();
-            for issue in listing.data {
-                let context_copy = context.clone();
-                let issue_id = issue.id.clone();
-                let project_id_copy = issue.project_id.clone();
+    Ok(())
+}
 
-                set.spawn(async move {
-                    fetch_issue(&context_copy, project_id_copy.as_str(), issue_id.as_str()).await
-                });
-            }
+/// Markdown comment that now and then mentions a project member, `body_data`
+/// lists the mentioned users
+fn random_comment(rng: &mut StdRng, members: &[ProjectMember]) -> CreateCommentBody {
+    let mention = get_random_item(rng, members, 50).and_then(|member| member.user.as_ref());
+
+    let mut body = format!(
+        "**{}**\n\n- {}\n- {}",
+        CatchPhrase().fake_with_rng::<String, _>(rng),
+        Sentence(3..8).fake_with_rng::<String, _>(rng),
+        Sentence(3..8).fake_with_rng::<String, _>(rng)
+    );
+    if let Some(user) = mention {
+        body.push_str(&format!(
+            "\n\n@{} {}",
+            user.username,
+            Sentence(3..8).fake_with_rng::<String, _>(rng)
+        ));
+    }
 
-            let req_count: u32 = set.len() as u32;
-            total_reqs += req_count;
-
-            // Process batch
-            while let Some(join_res) = set.join_next().await {
-                match join_res {
-                    Ok(res) => match res {
-                        Ok(issue_res) => {
-                            if let None = issue_res.data {
-                                failed += 1;
-                            }
-
-                            sum += issue_res.duration;
-
-                            if min_duration == 0 {
-                                min_duration = issue_res.duration;
-                            } else if issue_res.duration < min_duration {
-                                min_duration = issue_res.duration;
-                            }
-
-                            if issue_res.duration > max_duration {
-                                max_duration = issue_res.duration;
-                            }
-                        }
-                        Err(issue_err) => {
-                            error!("Error: {:?}", issue_err);
-                        }
-                    },
-                    Err(err) => {
-                        error!("Error: {:?}", err);
-                    }
-                }
-            }
+    let mentions: Vec<serde_json::Value> = mention
+        .iter()
+        .map(|user| serde_json::json!({ "id": user.id, "username": user.username }))
+        .collect();
+    let body_data = serde_json::json!({ "format": "markdown", "mentions": mentions });
 
-            // See if there are still more items
-            if listing.meta.total_pages > page {
-                page += 1;
-                has_more = true;
-            }
+    CreateCommentBody {
+        body,
+        body_data: Some(body_data.to_string()),
+    }
+}
+
+/// Pages through the backlog picking random issues for the active sprint, or
+/// the next planned one, until the estimates reach the sprint capacity then
+/// moves them
+async fn groom_backlog(
+    ctx: &AuthContext,
+    data: &ProjectData,
+    target: &MultiTargetConfig,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let project_id = data.project.id.as_str();
+    let backlog = &target.backlog;
+
+    let sprints = fetch_sprints(ctx, project_id).await?;
+    let sprint = match sprints.iter().find(|sprint| sprint.is_active()) {
+        Some(sprint) => sprint,
+        None => match sprints.iter().find(|sprint| sprint.is_planned()) {
+            Some(sprint) => sprint,
+            None => return Ok(()),
+        },
+    };
+
+    // Unestimated issues do not count against the capacity
+    let planned = fetch_sprint_issues(ctx, project_id, &sprint.id).await?;
+    let mut load: u32 = planned.iter().filter_map(|issue| issue.estimate).sum();
+
+    // Moving issues while paging would shift the next pages and skip issues
+    let mut picked: Vec<String> = Vec::new();
+    let mut page = 1;
+    while load < backlog.sprint_capacity {
+        let listing = fetch_backlog(ctx, project_id, page, 50).await?;
+        let issues = pick_backlog_issues(rng, &listing.data, backlog, &mut load);
+        picked.extend(issues.iter().map(|issue| issue.id.clone()));
+
+        if listing.meta.total_pages <= page {
+            break;
         }
+        page += 1;
     }
 
-    let succeed = total_reqs - failed;
-    let big_success_ratio =
-        (BigDecimal::from(succeed) / BigDecimal::from(total_reqs)) * BigDecimal::from(100);
-    let success_ratio = big_success_ratio.round(2);
-    let big_sum = BigDecimal::from(sum);
-    let big_total_reqs = BigDecimal::from(total_reqs);
-    let big_avg = big_sum / big_total_reqs.clone();
-    let avg = big_avg.round(2);
-
-    let total_time = timer.elapsed().as_millis();
-    let total_crawl_time = crawl_timer.elapsed().as_millis();
-    let big_crawl_total_time = BigDecimal::from(total_crawl_time);
-    let big_rps: BigDecimal = big_total_reqs / (big_crawl_total_time / 1000.0);
-    let rps = big_rps.round(2);
-
-    // Print stats
-    println!("");
-    println!("Total requests: {}", total_reqs);
-    println!("Succeed: {}", succeed);
-    println!("Failed: {}", failed);
-    println!("Success rate: {}%", success_ratio);
-    println!("Min: {} ms", min_duration);
-    println!("Avg: {} ms", avg);
-    println!("Max: {} ms", max_duration);
-    println!("Requests per second: {}", rps);
-    println!("Run duration: {} ms", total_time);
+    for issue_id in picked.iter() {
+        move_to_sprint(ctx, project_id, issue_id, &sprint.id).await?;
+    }
+    if !picked.is_empty() {
+        info!(
+            "{} planned {} backlog issues into {} of {} ({}/{})",
+            ctx.user.username,
+            picked.len(),
+            sprint.title,
+            data.project.key,
+            load,
+            backlog.sprint_capacity
+        );
+    }
 
     Ok(())
 }
 
-pub async fn run_user_tasks(config: &GlobalConfig, target: &SingleTargetConfig) -> Result<()> {
-    let captcha_token = create_captcha_token(&config.jwt_secret)?;
-    let auth_payload = AuthPayload {
-        username: target.username.clone(),
-        password: target.password.clone(),
-        captcha_token,
+/// Random backlog issues whose estimates still fit the sprint, `load` grows
+/// with every pick
+fn pick_backlog_issues<'a>(
+    rng: &mut StdRng,
+    issues: &'a [Issue],
+    backlog: &BacklogConfig,
+    load: &mut u32,
+) -> Vec<&'a Issue> {
+    let mut picked = Vec::new();
+    for issue in issues.iter() {
+        if *load >= backlog.sprint_capacity {
+            break;
+        }
+        if !get_item_chance(rng, backlog.pick_chance) {
+            continue;
+        }
+        let estimate = issue.estimate.unwrap_or(0);
+        if *load + estimate > backlog.sprint_capacity {
+            continue;
+        }
+        *load += estimate;
+        picked.push(issue);
+    }
+    picked
+}
+
+/// Advances a random subset of the active sprint board one column towards done
+async fn move_board_issues(ctx: &AuthContext, data: &ProjectData, rng: &mut StdRng) -> Result<()> {
+    let project_id = data.project.id.as_str();
+    let board = match fetch_board(ctx, project_id).await {
+        Ok(board) => board,
+        // Nothing to move until a sprint starts
+        Err(err) if err.is_not_found() => return Ok(()),
+        Err(err) => return Err(err.into()),
     };
-    let ctx = authenticate(&config.api_url, auth_payload).await?;
-    run_tasks(&ctx).await?;
+
+    let statuses = board_columns(data);
+    let mut moved = 0;
+    for issue in board.issues.iter() {
+        if !get_item_chance(rng, 30) {
+            continue;
+        }
+        if let Some(status) = next_status(statuses, issue) {
+            move_board_issue(ctx, project_id, &issue.id, &status.id).await?;
+            moved += 1;
+        }
+    }
+    if moved > 0 {
+        info!(
+            "{} moved {} issues on the {} board of {}",
+            ctx.user.username, moved, board.sprint.title, data.project.key
+        );
+    }
+
+    Ok(())
+}
+
+/// Ordered statuses of the project, from the preferences when not loaded
+fn board_columns(data: &ProjectData) -> &[IssueStatus] {
+    if !data.statuses.is_empty() {
+        return &data.statuses;
+    }
+    match data.project.preferences.as_ref() {
+        Some(pref) => &pref.issue_statuses,
+        None => &[],
+    }
+}
+
+/// Moves the project one step through its sprint cycle: keeps a few sprints
+/// planned ahead, completes the active sprint once all of its issues are done
+/// or its end date passed, then starts the next one for two weeks
+async fn cycle_sprints(ctx: &AuthContext, data: &ProjectData, rng: &mut StdRng) -> Result<()> {
+    let project_id = data.project.id.as_str();
+    let mut sprints = fetch_sprints(ctx, project_id).await?;
+
+    let planned = sprints.iter().filter(|sprint| sprint.is_planned()).count();
+    for _ in planned..PLANNED_SPRINTS {
+        let payload = CreateSprintBody {
+            title: format!("Sprint {}", sprints.len() + 1),
+            description: Some(CatchPhrase().fake_with_rng(rng)),
+        };
+        sprints.push(create_sprint(ctx, project_id, &payload).await?);
+    }
+
+    if let Some(active) = sprints.iter().find(|sprint| sprint.is_active()) {
+        // Last board column is done, an empty sprint waits for issues or its
+        // end date
+        let issues = fetch_sprint_issues(ctx, project_id, &active.id).await?;
+        let done = board_columns(data).last().map(|status| status.id.as_str());
+        let finished = !issues.is_empty()
+            && issues
+                .iter()
+                .all(|issue| done.is_some() && issue.status.as_deref() == done);
+        if !finished && !is_overdue(active) {
+            return Ok(());
+        }
+
+        complete_sprint(ctx, project_id, &active.id).await?;
+        info!(
+            "{} completed {} in {}",
+            ctx.user.username, active.title, data.project.key
+        );
+    }
+
+    if let Some(next) = sprints.iter().find(|sprint| sprint.is_planned()) {
+        let today = Utc::now().date_naive();
+        let payload = StartSprintBody {
+            start_date: today.format("%Y-%m-%d").to_string(),
+            end_date: (today + Days::new(SPRINT_DAYS))
+                .format("%Y-%m-%d")
+                .to_string(),
+        };
+        start_sprint(ctx, project_id, &next.id, &payload).await?;
+        info!(
+            "{} started {} in {}",
+            ctx.user.username, next.title, data.project.key
+        );
+    }
 
     Ok(())
 }
 
-async fn run_tasks(ctx: &AuthContext) -> Result<()> {
-    // Fetch IAM
-    // Fetch projects
-    // For each project, do:
-    // Fetch permissions
-    // Fetch statuses
-    // Fetch labels
-    // Fetch first 50 epics
-    // Fetch members
-    // Do actual tasks...
+/// Whether the end date of the sprint passed, dates may carry a time part
+fn is_overdue(sprint: &Sprint) -> bool {
+    let Some(end_date) = sprint.end_date.as_deref().and_then(|date| date.get(..10)) else {
+        return false;
+    };
+    NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .is_ok_and(|end_date| end_date < Utc::now().date_naive())
+}
+
+/// Visits the project pages and reads through its issues without changing
+/// anything, pausing after each page
+async fn browse_project<A: Access>(
+    ctx: &AuthContext<A>,
+    project: &Project,
+    think_time: &ThinkTime,
+    rng: &mut StdRng,
+) -> Result<()> {
+    let project_id = project.id.as_str();
+
+    let _ = fetch_project_authz(ctx, project_id).await?;
+    let data = load_project_data(ctx, project_id).await?;
+    info!(
+        "{} browsing {}: {}",
+        ctx.user.username, data.project.key, data.project.name
+    );
+    think_time.pause(rng).await;
+    let _ = fetch_project_channel(ctx, project_id).await?;
+    let _ = fetch_project_repositories(ctx, project_id).await?;
+    think_time.pause(rng).await;
+    let _ = fetch_sprints(ctx, project_id).await?;
+    let _ = fetch_backlog(ctx, project_id, 1, 50).await?;
+    think_time.pause(rng).await;
+    if let Err(err) = fetch_board(ctx, project_id).await
+        && !err.is_not_found()
+    {
+        return Err(err.into());
+    }
+    think_time.pause(rng).await;
+
+    // Paginate through the first few pages of issues, opening some of them
+    let mut page = 1;
+    loop {
+        let listing = fetch_issues(ctx, Some(project_id), page, 50).await?;
+        think_time.pause(rng).await;
+        for issue in listing.data.iter() {
+            if get_item_chance(rng, 20) {
+                let _ = fetch_issue(ctx, project_id, &issue.id).await?;
+                let _ = fetch_comments(ctx, project_id, &issue.id, 1, 20).await?;
+                think_time.pause(rng).await;
+            }
+        }
+
+        if listing.meta.total_pages <= page || page >= 3 {
+            return Ok(());
+        }
+        page += 1;
+    }
+}
+
+/// Visits the pages outside of projects, ie: notifications and account pages
+async fn browse_account<A: Access>(ctx: &AuthContext<A>) -> Result<()> {
+    let _ = fetch_iam(ctx).await?;
+    let _ = fetch_my_organisation(ctx).await?;
+    let _ = fetch_current_user(ctx).await?;
+    let _ = fetch_user_preferences(ctx).await?;
+    let _ = fetch_notifications(ctx, 1, 20).await?;
+    let _ = fetch_activity_logs(ctx, 1, 20).await?;
+
     Ok(())
 }
 
-fn get_item_chance(chance: u32) -> bool {
+/// Status right after the current one of the issue, none when already done
+fn next_status<'a>(statuses: &'a [IssueStatus], issue: &Issue) -> Option<&'a IssueStatus> {
+    let current = issue.status.as_deref()?;
+    let position = statuses.iter().position(|status| status.id == current)?;
+    statuses.get(position + 1)
+}
+
+/// Random stream of a single virtual user, the same seed and user position
+/// always make the same choices
+fn user_rng(seed: u64, user: u64) -> StdRng {
+    StdRng::seed_from_u64(seed ^ user.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+fn get_item_chance(rng: &mut StdRng, chance: u32) -> bool {
     if chance > 100 {
         panic!("Chance must be between 0 to 100")
     }
 
-    let value = rand::rng().random_range(0..=100);
-    value <= chance
+    let value = rng.random_range(0..100);
+    value < chance
 }
 
-fn get_random_item<T>(items: &Vec<T>, chance: u32) -> Option<&T> {
+fn get_random_item<'a, T>(rng: &mut StdRng, items: &'a [T], chance: u32) -> Option<&'a T> {
     let length = items.len();
-    let return_item = get_item_chance(chance);
+    let return_item = get_item_chance(rng, chance);
 
     if length > 0 && return_item {
         let max_length = length - 1;
-        let key = rand::rng().random_range(0..=max_length);
+        let key = rng.random_range(0..=max_length);
         return items.get(key);
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_limits() {
+        let once = RunLimits::default().once_by_default();
+        assert!(once.allows(0));
+        assert!(!once.allows(1));
+
+        let forever = RunLimits::new(Some(Duration::from_secs(60)), None, &Shutdown::default());
+        assert!(forever.once_by_default().allows(1000));
+
+        let expired = RunLimits::new(Some(Duration::ZERO), Some(5), &Shutdown::default());
+        assert!(expired.is_expired());
+        assert!(!expired.allows(0));
+    }
+
+    #[test]
+    fn test_item_chance() {
+        let mut rng = user_rng(42, 0);
+        assert!((0..1000).all(|_| !get_item_chance(&mut rng, 0)));
+        assert!((0..1000).all(|_| get_item_chance(&mut rng, 100)));
+
+        let hits = (0..10_000).filter(|_| get_item_chance(&mut rng, 1)).count();
+        assert!((50..200).contains(&hits));
+    }
+
+    #[test]
+    fn test_sprint_overdue() {
+        let mut sprint = Sprint {
+            id: "sp1".to_string(),
+            project_id: "p1".to_string(),
+            title: "Sprint 1".to_string(),
+            description: None,
+            start_date: None,
+            end_date: None,
+            status: Some("active".to_string()),
+            created_at: None,
+            updated_at: None,
+        };
+        assert!(!is_overdue(&sprint));
+
+        sprint.end_date = Some("2020-01-14T00:00:00.000Z".to_string());
+        assert!(is_overdue(&sprint));
+
+        let next_week = Utc::now().date_naive() + Days::new(7);
+        sprint.end_date = Some(next_week.format("%Y-%m-%d").to_string());
+        assert!(!is_overdue(&sprint));
+    }
+
+    #[test]
+    fn test_pick_backlog_issues() {
+        let issues: Vec<Issue> = serde_json::from_str(
+            r#"[{"id": "i1", "key": "PRJ-1", "projectId": "p1", "type": "task", "title": "A", "estimate": 3},
+                {"id": "i2", "key": "PRJ-2", "projectId": "p1", "type": "task", "title": "B", "estimate": 8},
+                {"id": "i3", "key": "PRJ-3", "projectId": "p1", "type": "task", "title": "C"},
+                {"id": "i4", "key": "PRJ-4", "projectId": "p1", "type": "task", "title": "D", "estimate": 5},
+                {"id": "i5", "key": "PRJ-5", "projectId": "p1", "type": "task", "title": "E", "estimate": 2},
+                {"id": "i6", "key": "PRJ-6", "projectId": "p1", "type": "task", "title": "F"}]"#,
+        )
+        .unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let backlog = BacklogConfig {
+            pick_chance: 100,
+            sprint_capacity: 10,
+        };
+
+        // Too big for what is left is skipped, nothing is picked once full
+        let mut load = 0;
+        let picked = pick_backlog_issues(&mut rng, &issues, &backlog, &mut load);
+        let ids: Vec<&str> = picked.iter().map(|issue| issue.id.as_str()).collect();
+        assert_eq!(ids, vec!["i1", "i3", "i4", "i5"]);
+        assert_eq!(load, 10);
+        assert!(pick_backlog_issues(&mut rng, &issues, &backlog, &mut load).is_empty());
+
+        let backlog = BacklogConfig {
+            pick_chance: 0,
+            sprint_capacity: 10,
+        };
+        let mut load = 0;
+        assert!(pick_backlog_issues(&mut rng, &issues, &backlog, &mut load).is_empty());
+    }
+
+    #[test]
+    fn test_check_issue_update() {
+        let issue: Issue = serde_json::from_str(
+            r#"{"id": "i1", "key": "PRJ-1", "projectId": "p1", "type": "task",
+                "title": "New title", "estimate": 5, "assigneeId": "u2"}"#,
+        )
+        .unwrap();
+
+        let update = IssuePartial {
+            title: Some("New title".to_string()),
+            estimate: Some(5),
+            ..IssuePartial::default()
+        };
+        assert!(check_issue_update(&update, &issue).is_ok());
+
+        let update = IssuePartial {
+            title: Some("New title".to_string()),
+            assignee_id: Some("u1".to_string()),
+            labels: Some(vec!["l1".to_string()]),
+            ..IssuePartial::default()
+        };
+        let err = check_issue_update(&update, &issue).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Update of PRJ-1 is not reflected in the response: labels, assignee"
+        );
+    }
+
+    #[test]
+    fn test_random_comment_mentions() {
+        let members: Vec<ProjectMember> = serde_json::from_str(
+            r#"[{"id": "m1", "user": {"id": "u1", "username": "alice", "status": "active"}}]"#,
+        )
+        .unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let comments: Vec<CreateCommentBody> = (0..20)
+            .map(|_| random_comment(&mut rng, &members))
+            .collect();
+        let mentioned = comments
+            .iter()
+            .find(|comment| comment.body.contains("@alice"))
+            .expect("Some comments mention a member");
+        assert!(mentioned.body.starts_with("**"));
+        let body_data: serde_json::Value =
+            serde_json::from_str(mentioned.body_data.as_deref().unwrap()).unwrap();
+        assert_eq!(body_data["mentions"][0]["id"], "u1");
+
+        let plain = random_comment(&mut rng, &[]);
+        assert!(!plain.body.contains('@'));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    error::ApiResult,
+    models::{
+        issue::Issue,
+        sprint::{CreateSprintBody, Sprint, StartSprintBody},
+    },
+};
+
+use super::{auth::AuthContext, client::Access};
+
+pub async fn fetch_sprints<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+) -> ApiResult<Vec<Sprint>> {
+    let url = format!("/projects/{}/sprints", project_id);
+    let request = ctx.client.get(&url);
+
+    ctx.client.send("sprints.list", request).await
+}
+
+pub async fn fetch_sprint_issues<A: Access>(
+    ctx: &AuthContext<A>,
+    project_id: &str,
+    sprint_id: &str,
+) -> ApiResult<Vec<Issue>> {
+    let url = format!("/projects/{}/issues", project_id);
+    let query_params = vec![
+        ("sprint_id", sprint_id.to_string()),
+        ("state", "active".to_string()),
+        ("sort", "-createdAt".to_string()),
+    ];
+    let request = ctx.client.get(&url).query(&query_params);
+
+    ctx.client.send("sprints.issues", request).await
+}
+
+pub async fn create_sprint(
+    ctx: &AuthContext,
+    project_id: &str,
+    payload: &CreateSprintBody,
+) -> ApiResult<Sprint> {
+    let url = format!("/projects/{}/sprints", project_id);
+    let request = ctx.client.post(&url).json(payload);
+
+    ctx.client.send("sprints.create", request).await
+}
+
+pub async fn start_sprint(
+    ctx: &AuthContext,
+    project_id: &str,
+    sprint_id: &str,
+    payload: &StartSprintBody,
+) -> ApiResult<Sprint> {
+    let url = format!("/projects/{}/sprints/{}/start", project_id, sprint_id);
+    let request = ctx.client.post(&url).json(payload);
+
+    ctx.client.send("sprints.start", request).await
+}
+
+pub async fn complete_sprint(
+    ctx: &AuthContext,
+    project_id: &str,
+    sprint_id: &str,
+) -> ApiResult<Sprint> {
+    let url = format!("/projects/{}/sprints/{}/complete", project_id, sprint_id);
+    let request = ctx.client.post(&url);
+
+    ctx.client.send("sprints.complete", request).await
+}
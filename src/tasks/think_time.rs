@@ -0,0 +1,81 @@
+use rand::Rng;
+use rand_distr::{Distribution, Exp, Normal};
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::config::ThinkTimeConfig;
+
+/// Samples the pause of a virtual user between two actions, the pause happens
+/// outside of requests so it never shows up in latency stats
+#[derive(Clone, Debug)]
+pub struct ThinkTime {
+    config: ThinkTimeConfig,
+}
+
+impl From<&ThinkTimeConfig> for ThinkTime {
+    fn from(config: &ThinkTimeConfig) -> Self {
+        ThinkTime { config: *config }
+    }
+}
+
+impl ThinkTime {
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Duration {
+        let millis = match self.config {
+            ThinkTimeConfig::None => return Duration::ZERO,
+            ThinkTimeConfig::Fixed { ms } => ms as f64,
+            ThinkTimeConfig::Uniform { min_ms, max_ms } => {
+                rng.random_range(min_ms..=max_ms.max(min_ms)) as f64
+            }
+            ThinkTimeConfig::Normal { mean_ms, stdev_ms } => {
+                match Normal::new(mean_ms as f64, stdev_ms as f64) {
+                    Ok(normal) => normal.sample(rng),
+                    Err(_) => mean_ms as f64,
+                }
+            }
+            ThinkTimeConfig::Exponential { mean_ms } => {
+                match Exp::new(1.0 / mean_ms.max(1) as f64) {
+                    Ok(exp) => exp.sample(rng),
+                    Err(_) => mean_ms as f64,
+                }
+            }
+        };
+
+        Duration::from_millis(millis.max(0.0).round() as u64)
+    }
+
+    pub async fn pause<R: Rng + ?Sized>(&self, rng: &mut R) {
+        let delay = self.sample(rng);
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_think_time_samples() {
+        let mut rng = rand::rng();
+        let none = ThinkTime::from(&ThinkTimeConfig::None);
+        assert_eq!(none.sample(&mut rng), Duration::ZERO);
+
+        let fixed = ThinkTime::from(&ThinkTimeConfig::Fixed { ms: 250 });
+        assert_eq!(fixed.sample(&mut rng), Duration::from_millis(250));
+
+        let uniform = ThinkTime::from(&ThinkTimeConfig::Uniform {
+            min_ms: 100,
+            max_ms: 200,
+        });
+        for _ in 0..100 {
+            let delay = uniform.sample(&mut rng);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+
+        let exponential = ThinkTime::from(&ThinkTimeConfig::Exponential { mean_ms: 100 });
+        let total: Duration = (0..1000).map(|_| exponential.sample(&mut rng)).sum();
+        let mean = total.as_millis() / 1000;
+        assert!(mean > 50 && mean < 150);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::{
+    error::ApiResult,
+    models::{activity_log::ActivityLog, pagination::PaginationResult},
+};
+
+use super::{auth::AuthContext, client::Access};
+
+pub async fn fetch_activity_logs<A: Access>(
+    ctx: &AuthContext<A>,
+    page: u32,
+    per_page: u32,
+) -> ApiResult<PaginationResult<ActivityLog>> {
+    let query_params = vec![
+        ("page", page.to_string()),
+        ("per_page", per_page.to_string()),
+        ("sort", "-createdAt".to_string()),
+    ];
+    let request = ctx.client.get("/activityLogs").query(&query_params);
+
+    ctx.client.send("activity_logs.list", request).await
+}
@@ -41,7 +41,7 @@ pub fn verify_captcha_token(token: &str, secret: &str) -> Result<String> {
         return Err(anyhow!("Error decoding token"));
     };
 
-    if decoded.claims.sub.len() == 0 {
+    if decoded.claims.sub.is_empty() {
         return Err(anyhow!("Invalid token"));
     }
 
@@ -56,7 +56,7 @@ mod tests {
     fn test_create_captcha_token() {
         // Generate token
         let token = create_captcha_token("secret").unwrap();
-        assert!(token.len() > 0);
+        assert!(!token.is_empty());
         assert!(token.starts_with("x-client-login:"));
 
         // Validate claims